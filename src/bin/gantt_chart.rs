@@ -11,13 +11,13 @@ impl GanttChartLogger {
 }
 
 impl GanttChartLog for GanttChartLogger {
-    fn output(self: &Self, args: Arguments) {
+    fn output(&self, args: Arguments) {
         println!("{}", args);
     }
-    fn warning(self: &Self, args: Arguments) {
+    fn warning(&self, args: Arguments) {
         eprintln!("{}", format!("warning: {}", args).yellow());
     }
-    fn error(self: &Self, args: Arguments) {
+    fn error(&self, args: Arguments) {
         eprintln!("{}", format!("error: {}", args).red());
     }
 }
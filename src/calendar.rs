@@ -0,0 +1,160 @@
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// How an item duration is counted
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum DurationUnit {
+    /// Every day counts, but the item is stretched over holidays and never ends on a non-working day
+    #[default]
+    CalendarDays,
+    /// Only working days count
+    Workdays,
+}
+
+/// The days of a normal working week
+pub static DEFAULT_WORK_WEEK: [Weekday; 5] = [
+    Weekday::Mon,
+    Weekday::Tue,
+    Weekday::Wed,
+    Weekday::Thu,
+    Weekday::Fri,
+];
+
+/// The working and non-working days used to schedule items
+#[derive(Debug, Clone)]
+pub struct Calendar {
+    // Indexed by the number of days from Monday
+    work_week: [bool; 7],
+    holidays: HashSet<NaiveDate>,
+    vacations: HashSet<NaiveDate>,
+    // Percentage of each working day that is available for work
+    availability: u32,
+}
+
+impl Default for Calendar {
+    fn default() -> Self {
+        Calendar::new(&DEFAULT_WORK_WEEK, HashSet::new())
+    }
+}
+
+impl Calendar {
+    pub fn new(work_week: &[Weekday], holidays: HashSet<NaiveDate>) -> Calendar {
+        let mut days = [false; 7];
+
+        for day in work_week.iter() {
+            days[day.num_days_from_monday() as usize] = true;
+        }
+
+        Calendar {
+            work_week: days,
+            holidays,
+            vacations: HashSet::new(),
+            availability: 100,
+        }
+    }
+
+    pub fn with_work_week(self, work_week: &[Weekday]) -> Calendar {
+        Calendar {
+            vacations: self.vacations,
+            availability: self.availability,
+            ..Calendar::new(work_week, self.holidays)
+        }
+    }
+
+    pub fn with_vacations(mut self, vacations: HashSet<NaiveDate>) -> Calendar {
+        self.vacations = vacations;
+        self
+    }
+
+    pub fn with_availability(mut self, availability: u32) -> Calendar {
+        self.availability = availability.max(1);
+        self
+    }
+
+    pub fn is_holiday(&self, date: NaiveDate) -> bool {
+        self.holidays.contains(&date)
+    }
+
+    pub fn is_vacation(&self, date: NaiveDate) -> bool {
+        self.vacations.contains(&date)
+    }
+
+    /// Whether the date is a holiday or vacation day
+    pub fn is_day_off(&self, date: NaiveDate) -> bool {
+        self.is_holiday(date) || self.is_vacation(date)
+    }
+
+    /// Whether the date falls outside the work week
+    pub fn is_weekend(&self, date: NaiveDate) -> bool {
+        !self.work_week[date.weekday().num_days_from_monday() as usize]
+    }
+
+    pub fn is_working_day(&self, date: NaiveDate) -> bool {
+        !self.is_weekend(date) && !self.is_day_off(date)
+    }
+
+    // Part-time resources need more days to do the same amount of work
+    fn available_days(&self, days: i64) -> i64 {
+        let availability = self.availability as i64;
+
+        (days * 100 + availability - 1) / availability
+    }
+
+    /// The given date, or the first working day after it
+    pub fn next_working_day(&self, mut date: NaiveDate) -> NaiveDate {
+        while !self.is_working_day(date) {
+            date = date.succ();
+        }
+
+        date
+    }
+
+    /// The number of calendar days from `start_date` needed to complete `days` of work, up to
+    /// the next working day, when following work can start
+    pub fn elapsed_days(&self, start_date: NaiveDate, days: i64, unit: DurationUnit) -> i64 {
+        let mut end_date = start_date;
+        let mut days_left = self.available_days(days);
+
+        while days_left > 0 {
+            let counts = match unit {
+                DurationUnit::CalendarDays => {
+                    !self.is_day_off(end_date) || self.is_weekend(end_date)
+                }
+                DurationUnit::Workdays => self.is_working_day(end_date),
+            };
+
+            if counts {
+                days_left -= 1;
+            }
+
+            end_date = end_date.succ();
+        }
+
+        (self.next_working_day(end_date) - start_date).num_days()
+    }
+
+    /// The date to start `days` of work so that it completes by `end_date`
+    pub fn start_date_for(&self, end_date: NaiveDate, days: i64, unit: DurationUnit) -> NaiveDate {
+        let days = self.available_days(days);
+
+        match unit {
+            DurationUnit::CalendarDays => end_date - Duration::days(days),
+            DurationUnit::Workdays => {
+                let mut start_date = end_date;
+                let mut days_left = days;
+
+                while days_left > 0 {
+                    start_date = start_date.pred();
+
+                    if self.is_working_day(start_date) {
+                        days_left -= 1;
+                    }
+                }
+
+                start_date
+            }
+        }
+    }
+}
@@ -0,0 +1,276 @@
+use crate::DependencyKind;
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+use serde::{Deserialize, Serialize};
+use std::{fmt, str::FromStr};
+
+// The date that an expression is relative to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DateBase {
+    Date(NaiveDate),
+    Today,
+    // The first such day after today
+    Next(Weekday),
+    StartOfPreviousItem,
+    EndOfPreviousItem,
+}
+
+/// A date in the chart, written either as a date or relative to today or the previous item,
+/// for example `2026-07-01`, `today`, `+2w`, `next monday`, `2026-Q3` or
+/// `end of previous item + 3d`
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(try_from = "String", into = "String")]
+pub struct DateExpr {
+    text: String,
+    base: DateBase,
+    offset: i64,
+}
+
+impl DateExpr {
+    /// The date, or `None` if it depends on the schedule of the previous item, or an error if
+    /// the offset takes it outside the range of dates
+    pub fn resolve(&self, today: NaiveDate) -> Result<Option<NaiveDate>, String> {
+        let (date, offset) = match self.base {
+            DateBase::Date(date) => (date, self.offset),
+            DateBase::Today => (today, self.offset),
+            DateBase::Next(weekday) => {
+                let days = (weekday.num_days_from_monday() as i64
+                    - today.weekday().num_days_from_monday() as i64)
+                    .rem_euclid(7);
+
+                (
+                    today,
+                    self.offset.saturating_add(if days == 0 { 7 } else { days }),
+                )
+            }
+            DateBase::StartOfPreviousItem | DateBase::EndOfPreviousItem => return Ok(None),
+        };
+
+        // No date is more than an i32 of days from another, and larger offsets would overflow
+        // the duration
+        i32::try_from(offset)
+            .ok()
+            .and_then(|days| date.checked_add_signed(Duration::days(days.into())))
+            .map(Some)
+            .ok_or_else(|| format!("'{}' is outside the range of dates", self.text))
+    }
+
+    /// The link to the previous item, and its lag, if the date is relative to that item
+    pub fn previous_item_link(&self) -> Option<(DependencyKind, i64)> {
+        match self.base {
+            DateBase::StartOfPreviousItem => Some((DependencyKind::StartToStart, self.offset)),
+            DateBase::EndOfPreviousItem => Some((DependencyKind::FinishToStart, self.offset)),
+            _ => None,
+        }
+    }
+}
+
+// Split a trailing offset like `+ 3d` or `-2w` off the end of the text
+fn split_offset(text: &str) -> Option<(&str, i64)> {
+    let (rest, days_per_unit) = match text.strip_suffix('d') {
+        Some(rest) => (rest, 1),
+        None => (text.strip_suffix('w')?, 7),
+    };
+    let number_start = rest.trim_end_matches(|c: char| c.is_ascii_digit()).len();
+    let number: i64 = rest[number_start..].parse().ok()?;
+    let days = number.checked_mul(days_per_unit)?;
+    let rest = rest[..number_start].trim_end();
+
+    if let Some(rest) = rest.strip_suffix('+') {
+        Some((rest.trim_end(), days))
+    } else {
+        let rest = rest.strip_suffix('-')?;
+
+        Some((rest.trim_end(), -days))
+    }
+}
+
+fn parse_base(text: &str) -> Option<DateBase> {
+    match text {
+        "" | "today" => return Some(DateBase::Today),
+        "start of previous item" => return Some(DateBase::StartOfPreviousItem),
+        "end of previous item" => return Some(DateBase::EndOfPreviousItem),
+        _ => (),
+    }
+
+    if let Some(weekday) = text.strip_prefix("next ") {
+        return Weekday::from_str(weekday.trim()).ok().map(DateBase::Next);
+    }
+
+    if let Some((year, quarter)) = text.split_once("-q") {
+        let quarter: u32 = quarter.parse().ok().filter(|q| (1..=4).contains(q))?;
+
+        return NaiveDate::from_ymd_opt(year.parse().ok()?, quarter * 3 - 2, 1).map(DateBase::Date);
+    }
+
+    NaiveDate::parse_from_str(text, "%Y-%m-%d")
+        .ok()
+        .map(DateBase::Date)
+}
+
+impl FromStr for DateExpr {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let lower = text.trim().to_ascii_lowercase();
+        let mut rest = lower.as_str();
+        let mut offset = Some(0i64);
+
+        while let Some((before, days)) = split_offset(rest) {
+            rest = before;
+            offset = offset.and_then(|offset| offset.checked_add(days));
+        }
+
+        match (parse_base(rest), offset) {
+            (Some(base), Some(offset)) if !lower.is_empty() => Ok(DateExpr {
+                text: text.to_owned(),
+                base,
+                offset,
+            }),
+            _ => Err(format!(
+                "'{}' is not a date like '2026-07-01', '2026-Q3', 'today + 2w', 'next monday' or \
+                 'end of previous item + 3d'",
+                text
+            )),
+        }
+    }
+}
+
+impl TryFrom<String> for DateExpr {
+    type Error = String;
+
+    fn try_from(text: String) -> Result<Self, Self::Error> {
+        text.parse()
+    }
+}
+
+impl From<DateExpr> for String {
+    fn from(expr: DateExpr) -> Self {
+        expr.text
+    }
+}
+
+impl From<NaiveDate> for DateExpr {
+    fn from(date: NaiveDate) -> Self {
+        DateExpr {
+            text: date.to_string(),
+            base: DateBase::Date(date),
+            offset: 0,
+        }
+    }
+}
+
+impl fmt::Display for DateExpr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(text: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(text, "%Y-%m-%d").unwrap()
+    }
+
+    // 2026-10-14 is a Wednesday
+    fn resolve(text: &str) -> Option<NaiveDate> {
+        text.parse::<DateExpr>()
+            .unwrap()
+            .resolve(date("2026-10-14"))
+            .unwrap()
+    }
+
+    #[test]
+    fn resolves_dates_and_quarters() {
+        assert_eq!(resolve("2026-07-01"), Some(date("2026-07-01")));
+        assert_eq!(resolve("2026-Q3"), Some(date("2026-07-01")));
+        assert_eq!(resolve("2026-q1"), Some(date("2026-01-01")));
+        assert_eq!(resolve("2026-Q3 + 2w"), Some(date("2026-07-15")));
+        assert_eq!(resolve("2026-07-01-3d"), Some(date("2026-06-28")));
+    }
+
+    #[test]
+    fn resolves_relative_to_today() {
+        assert_eq!(resolve("today"), Some(date("2026-10-14")));
+        assert_eq!(resolve(" Today "), Some(date("2026-10-14")));
+        assert_eq!(resolve("+2w"), Some(date("2026-10-28")));
+        assert_eq!(resolve("-1d"), Some(date("2026-10-13")));
+        assert_eq!(resolve("today+2w"), Some(date("2026-10-28")));
+        assert_eq!(resolve("today + 1w - 2d + 3d"), Some(date("2026-10-22")));
+    }
+
+    #[test]
+    fn resolves_next_weekday_after_today() {
+        assert_eq!(resolve("next monday"), Some(date("2026-10-19")));
+        assert_eq!(resolve("next Thu"), Some(date("2026-10-15")));
+        assert_eq!(resolve("next wednesday"), Some(date("2026-10-21")));
+        assert_eq!(resolve("next wed + 1d"), Some(date("2026-10-22")));
+    }
+
+    #[test]
+    fn links_to_previous_item() {
+        let link = |text: &str| text.parse::<DateExpr>().unwrap().previous_item_link();
+
+        assert_eq!(resolve("end of previous item"), None);
+        assert_eq!(
+            link("end of previous item + 3d"),
+            Some((DependencyKind::FinishToStart, 3))
+        );
+        assert_eq!(
+            link("Start of previous item - 1w"),
+            Some((DependencyKind::StartToStart, -7))
+        );
+        assert_eq!(link("today"), None);
+    }
+
+    #[test]
+    fn rejects_bad_dates() {
+        for text in [
+            "",
+            "2026-13-01",
+            "2026-Q5",
+            "2026-Q0",
+            "next funday",
+            "today + 2",
+            "today + x",
+            "2w",
+            "end of item",
+            "today + 9223372036854775807w",
+            "today + 9223372036854775807d + 1d",
+        ] {
+            assert_eq!(
+                text.parse::<DateExpr>(),
+                Err(format!(
+                    "'{}' is not a date like '2026-07-01', '2026-Q3', 'today + 2w', 'next monday' \
+                     or 'end of previous item + 3d'",
+                    text
+                ))
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_offsets_outside_the_range_of_dates() {
+        for text in [
+            "today + 99999999d",
+            "2026-07-01 - 9999999999d",
+            "next monday + 9223372036854775807d",
+        ] {
+            assert_eq!(
+                text.parse::<DateExpr>()
+                    .unwrap()
+                    .resolve(date("2026-10-14")),
+                Err(format!("'{}' is outside the range of dates", text))
+            );
+        }
+    }
+
+    #[test]
+    fn keeps_the_original_text() {
+        let expr: DateExpr = json5::from_str("\"Today + 2w\"").unwrap();
+
+        assert_eq!(json5::to_string(&expr).unwrap(), "\"Today + 2w\"");
+        assert_eq!(DateExpr::from(date("2026-07-01")).to_string(), "2026-07-01");
+    }
+}
@@ -1,50 +1,191 @@
 /// Generate a Gantt chart
-use chrono::{Datelike, Duration, NaiveDate, Weekday};
-use clap::Parser;
+use chrono::{Datelike, Duration, Local, NaiveDate, Weekday};
+use clap::{Parser, Subcommand, ValueEnum};
 use core::fmt::Arguments;
 use easy_error::{self, bail, ResultExt};
 use rand::prelude::*;
 use serde::{Deserialize, Serialize};
 use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, HashSet, VecDeque},
     error::Error,
     fs::File,
     io::{self, Read, Write},
-    path::PathBuf,
-};
-use svg::{
-    node::{element::path::Data, Node, *},
-    Document,
+    path::{Path, PathBuf},
 };
 
+mod calendar;
+mod date_expr;
 mod log_macros;
+mod renderer;
+mod splice;
+
+pub use calendar::{Calendar, DurationUnit, DEFAULT_WORK_WEEK};
+pub use date_expr::DateExpr;
+#[cfg(feature = "pdf")]
+pub use renderer::PdfRenderer;
+#[cfg(feature = "png")]
+pub use renderer::PngRenderer;
+pub use renderer::{
+    AsciiRenderer, HtmlRenderer, Orientation, PaperSize, Renderer, SvgRenderer, TermRenderer,
+};
+pub use splice::splice_baseline;
 
-static GOLDEN_RATIO_CONJUGATE: f32 = 0.618033988749895;
+static GOLDEN_RATIO_CONJUGATE: f32 = 0.618_034;
 static MONTH_NAMES: [&str; 12] = [
     "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
 ];
+// The longest lag between linked items, a century either way, so that dates stay in range
+static MAX_LAG_DAYS: u64 = 36_525;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum OutputFormat {
+    /// A static SVG image
+    Svg,
+    /// An HTML page containing the chart, with collapsible groups, a resource filter and zooming
+    Html,
+    /// A PNG bitmap, when built with the png feature
+    Png,
+    /// A PDF document split into pages, when built with the pdf feature
+    Pdf,
+    /// Colored text for a terminal
+    Term,
+    /// Plain ASCII text that is the same whatever the terminal
+    Ascii,
+}
 
 #[derive(Parser)]
 #[clap(version, about, long_about = None)]
 struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// Specify the JSON data file
     #[arg(value_name = "INPUT_FILE")]
     input_file: Option<PathBuf>,
 
-    /// The SVG output file
+    /// The output file
     #[arg(value_name = "OUTPUT_FILE")]
     output_file: Option<PathBuf>,
 
+    /// The format of the output file
+    #[arg(value_enum, short, long, default_value_t = OutputFormat::Svg)]
+    format: OutputFormat,
+
+    /// The number of PNG pixels for each SVG pixel
+    #[arg(value_name = "SCALE", long, default_value_t = 1.0)]
+    scale: f32,
+
+    /// The resolution of the PNG image, instead of the scale
+    #[arg(value_name = "DPI", long, conflicts_with = "scale")]
+    dpi: Option<f32>,
+
+    /// A CSS color to fill the image with, or 'none'. PNG images are white by default.
+    #[arg(value_name = "COLOR", long)]
+    background: Option<String>,
+
+    /// The paper size of PDF pages
+    #[arg(value_enum, long, default_value_t = PaperSize::A4)]
+    paper: PaperSize,
+
+    /// The orientation of PDF pages
+    #[arg(value_enum, long, default_value_t = Orientation::Landscape)]
+    orientation: Orientation,
+
+    /// The number of characters in each line of text, otherwise the width of the terminal, or 80 for ASCII
+    #[arg(value_name = "CHARS", long)]
+    width: Option<usize>,
+
     /// The width of the item title column
     #[arg(value_name = "WIDTH", short, long, default_value_t = 210.0)]
     title_width: f32,
 
-    /// The maximum width of each month
+    /// The maximum width of each month, or of each column with another time scale
     #[arg(value_name = "WIDTH", short, long, default_value_t = 80.0)]
     max_month_width: f32,
 
+    /// How much time each column covers. Months by default, or longer periods if months would be too narrow for text.
+    #[arg(value_enum, long)]
+    time_scale: Option<TimeScale>,
+
+    /// Start the chart on this date, cutting off anything before it
+    #[arg(value_name = "DATE", long)]
+    from: Option<NaiveDate>,
+
+    /// End the chart on this date, cutting off anything after it
+    #[arg(value_name = "DATE", long)]
+    to: Option<NaiveDate>,
+
+    /// The date to use for today in relative dates such as 'today + 2w'
+    #[arg(value_name = "DATE", long)]
+    today: Option<NaiveDate>,
+
     /// Add a resource table at the bottom of the graph
     #[arg(short, long, default_value_t = false)]
     add_resource_table: bool,
+
+    /// Highlight the items on the critical path
+    #[arg(short, long, default_value_t = false)]
+    critical_path: bool,
+
+    /// Shade the non-working days of the resource in each row
+    #[arg(short, long, default_value_t = false)]
+    shade_weekends: bool,
+
+    /// Delay lower priority items until their resource is free
+    #[arg(short, long, default_value_t = false)]
+    level: bool,
+
+    /// The baseline to compare the schedule against, otherwise the last one in the chart
+    #[arg(value_name = "NAME", short, long)]
+    baseline: Option<String>,
+
+    /// An iCalendar file or list of dates to add to the chart holidays
+    #[arg(value_name = "HOLIDAYS_FILE", long = "holidays")]
+    holidays_file: Option<PathBuf>,
+
+    /// A JSON5 file of baselines to add to the chart baselines
+    #[arg(value_name = "BASELINE_FILE", long = "baseline-file")]
+    baseline_file: Option<PathBuf>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Work with saved copies of the schedule
+    Baseline {
+        #[command(subcommand)]
+        command: BaselineCommand,
+    },
+}
+
+#[derive(Subcommand)]
+enum BaselineCommand {
+    /// Save the scheduled dates of every item as a named baseline in the chart file
+    Save {
+        /// The name of the baseline, replacing any baseline with the same name
+        #[arg(value_name = "NAME")]
+        name: String,
+
+        /// The JSON data file
+        #[arg(value_name = "INPUT_FILE")]
+        input_file: PathBuf,
+
+        /// Save the baseline in this JSON5 file instead of the chart file
+        #[arg(value_name = "BASELINE_FILE", short, long)]
+        output_file: Option<PathBuf>,
+
+        /// Delay lower priority items until their resource is free
+        #[arg(short, long, default_value_t = false)]
+        level: bool,
+
+        /// An iCalendar file or list of dates to add to the chart holidays
+        #[arg(value_name = "HOLIDAYS_FILE", long = "holidays")]
+        holidays_file: Option<PathBuf>,
+
+        /// The date to use for today in relative dates such as 'today + 2w'
+        #[arg(value_name = "DATE", long)]
+        today: Option<NaiveDate>,
+    },
 }
 
 impl Cli {
@@ -70,44 +211,432 @@ impl Cli {
             None => Ok(Box::new(io::stdin())),
         }
     }
+
+    fn get_holidays_input(&self) -> Result<Option<Box<dyn Read>>, Box<dyn Error>> {
+        match self.holidays_file {
+            Some(ref path) => File::open(path)
+                .context(format!("Unable to open file '{}'", path.to_string_lossy()))
+                .map(|f| Some(Box::new(f) as Box<dyn Read>))
+                .map_err(|e| Box::new(e) as Box<dyn Error>),
+            None => Ok(None),
+        }
+    }
+
+    fn get_baseline_input(&self) -> Result<Option<Box<dyn Read>>, Box<dyn Error>> {
+        match self.baseline_file {
+            Some(ref path) => File::open(path)
+                .context(format!("Unable to open file '{}'", path.to_string_lossy()))
+                .map(|f| Some(Box::new(f) as Box<dyn Read>))
+                .map_err(|e| Box::new(e) as Box<dyn Error>),
+            None => Ok(None),
+        }
+    }
+}
+
+// A file of baselines kept apart from the chart
+#[derive(Deserialize, Debug)]
+struct BaselineFileData {
+    #[serde(default)]
+    baselines: Vec<BaselineData>,
 }
 
 pub trait GanttChartLog {
-    fn output(self: &Self, args: Arguments);
-    fn warning(self: &Self, args: Arguments);
-    fn error(self: &Self, args: Arguments);
+    fn output(&self, args: Arguments);
+    fn warning(&self, args: Arguments);
+    fn error(&self, args: Arguments);
 }
 
 pub struct GanttChartTool<'a> {
     log: &'a dyn GanttChartLog,
+    today: NaiveDate,
+}
+
+/// How the dates of two linked items constrain each other
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DependencyKind {
+    #[default]
+    #[serde(rename = "FS", alias = "finishToStart")]
+    FinishToStart,
+    #[serde(rename = "SS", alias = "startToStart")]
+    StartToStart,
+    #[serde(rename = "FF", alias = "finishToFinish")]
+    FinishToFinish,
+    #[serde(rename = "SF", alias = "startToFinish")]
+    StartToFinish,
+}
+
+/// A link to a predecessor item, written either as a bare id or as an object
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(from = "DependencyDef")]
+pub struct Dependency {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub kind: DependencyKind,
+    pub lag: i64,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum DependencyDef {
+    Id(String),
+    Link {
+        id: String,
+        #[serde(rename = "type", default)]
+        kind: DependencyKind,
+        #[serde(default)]
+        lag: i64,
+    },
+}
+
+impl From<DependencyDef> for Dependency {
+    fn from(def: DependencyDef) -> Self {
+        match def {
+            DependencyDef::Id(id) => Dependency {
+                id,
+                kind: DependencyKind::default(),
+                lag: 0,
+            },
+            DependencyDef::Link { id, kind, lag } => Dependency { id, kind, lag },
+        }
+    }
+}
+
+/// A share of a resource given to an item, written either as a bare resource index or as an object
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(from = "AssignmentDef")]
+pub struct Assignment {
+    #[serde(rename = "resource")]
+    pub resource_index: usize,
+    /// Percentage of the resource's time the item needs
+    pub allocation: u32,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum AssignmentDef {
+    Index(usize),
+    Share {
+        resource: usize,
+        #[serde(default = "full_allocation")]
+        allocation: u32,
+    },
+}
+
+fn full_allocation() -> u32 {
+    100
+}
+
+impl From<AssignmentDef> for Assignment {
+    fn from(def: AssignmentDef) -> Self {
+        match def {
+            AssignmentDef::Index(resource_index) => Assignment {
+                resource_index,
+                allocation: full_allocation(),
+            },
+            AssignmentDef::Share {
+                resource,
+                allocation,
+            } => Assignment {
+                resource_index: resource,
+                allocation,
+            },
+        }
+    }
+}
+
+// An item `resource` can be a single assignment or a list of them
+fn deserialize_assignments<'de, D>(deserializer: D) -> Result<Vec<Assignment>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum AssignmentsDef {
+        One(Assignment),
+        Many(Vec<Assignment>),
+    }
+
+    Ok(match AssignmentsDef::deserialize(deserializer)? {
+        AssignmentsDef::One(assignment) => vec![assignment],
+        AssignmentsDef::Many(assignments) => assignments,
+    })
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct ItemData {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
     pub title: String,
     pub duration: Option<i64>,
     #[serde(rename = "startDate", skip_serializing_if = "Option::is_none")]
-    pub start_date: Option<NaiveDate>,
-    #[serde(rename = "resource")]
-    pub resource_index: Option<usize>,
+    pub start_date: Option<DateExpr>,
+    #[serde(
+        rename = "resource",
+        default,
+        deserialize_with = "deserialize_assignments",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub assignments: Vec<Assignment>,
     pub open: Option<bool>,
+    /// Percentage of the item that is complete
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub progress: Option<u32>,
+    #[serde(rename = "dependsOn", default, skip_serializing_if = "Vec::is_empty")]
+    pub depends_on: Vec<Dependency>,
+    #[serde(rename = "durationUnit", skip_serializing_if = "Option::is_none")]
+    pub duration_unit: Option<DurationUnit>,
+    /// Higher priority items keep their dates when resources are leveled
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub priority: Option<u32>,
+    /// Child items, which make this a summary item spanning all of them
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub items: Vec<ItemData>,
+}
+
+impl ItemData {
+    /// The name used to refer to this item in messages
+    pub fn name(&self) -> &str {
+        self.id.as_deref().unwrap_or(&self.title)
+    }
 }
 
 #[derive(Deserialize, Serialize, Debug)]
 pub struct ChartData {
     pub title: String,
     #[serde(rename = "markedDate")]
-    pub marked_date: Option<NaiveDate>,
-    pub resources: Vec<String>,
+    pub marked_date: Option<DateExpr>,
+    pub resources: Vec<ResourceData>,
     pub items: Vec<ItemData>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub holidays: Vec<HolidayData>,
+    #[serde(rename = "durationUnit", skip_serializing_if = "Option::is_none")]
+    pub duration_unit: Option<DurationUnit>,
+    #[serde(rename = "workWeek", skip_serializing_if = "Option::is_none")]
+    pub work_week: Option<Vec<Weekday>>,
+    /// Saved schedules to compare against, oldest first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub baselines: Vec<BaselineData>,
+    #[serde(rename = "fiscalYear", skip_serializing_if = "Option::is_none")]
+    pub fiscal_year: Option<FiscalYearData>,
+}
+
+/// When the financial year starts, and how its years and quarters are labelled
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FiscalYearData {
+    /// The month the year starts in, from 1 for January
+    pub start_month: u32,
+    /// The label of each year, where `{yyyy}` or `{yy}` is the calendar year the fiscal year ends in
+    #[serde(default = "default_fiscal_year_format")]
+    pub year_format: String,
+    /// The label of each quarter, where `{q}` is the quarter of the fiscal year
+    #[serde(default = "default_fiscal_quarter_format")]
+    pub quarter_format: String,
+}
+
+fn default_fiscal_year_format() -> String {
+    "FY{yy}".to_owned()
+}
+
+fn default_fiscal_quarter_format() -> String {
+    "Q{q}".to_owned()
+}
+
+impl Default for FiscalYearData {
+    // The calendar year
+    fn default() -> Self {
+        FiscalYearData {
+            start_month: 1,
+            year_format: "{yyyy}".to_owned(),
+            quarter_format: "Q{q}".to_owned(),
+        }
+    }
+}
+
+// The number of months from the start of year zero to the month of a date
+fn month_index(date: NaiveDate) -> i32 {
+    date.year() * 12 + date.month0() as i32
+}
+
+// The first day of a month given as a month index
+fn month_start(month: i32) -> NaiveDate {
+    NaiveDate::from_ymd(month.div_euclid(12), month.rem_euclid(12) as u32 + 1, 1)
+}
+
+impl FiscalYearData {
+    // Fill in the fiscal year and quarter of a date in a label format
+    fn format(&self, format: &str, date: NaiveDate) -> String {
+        let month = month_index(date);
+        let months_into_year = (month - self.start_month as i32 + 1).rem_euclid(12);
+        // Fiscal years are named after the calendar year of their last month
+        let year = (month - months_into_year + 11).div_euclid(12);
+        let quarter = months_into_year / 3 + 1;
+
+        format
+            .replace("{yyyy}", &year.to_string())
+            .replace("{yy}", &format!("{:02}", year.rem_euclid(100)))
+            .replace("{q}", &quarter.to_string())
+    }
+}
+
+/// A named snapshot of the schedule
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct BaselineData {
+    pub name: String,
+    pub items: Vec<BaselineItemData>,
+}
+
+/// The dates of an item when a baseline was saved
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct BaselineItemData {
+    /// The id of the item, or its title if it has no id
+    pub id: String,
+    #[serde(rename = "startDate")]
+    pub start_date: NaiveDate,
+    /// The first working day after the item finishes, like the end date in the schedule, which
+    /// is the same as the start date for milestones
+    #[serde(rename = "endDate")]
+    pub end_date: NaiveDate,
+}
+
+// The names that are used more than once, quoted and in the order they are first repeated
+fn duplicate_names<'b>(names: impl IntoIterator<Item = &'b str>) -> Vec<String> {
+    let mut seen = HashSet::new();
+    let mut duplicates = vec![];
+
+    for name in names {
+        let quoted = format!("'{}'", name);
+
+        if !seen.insert(name) && !duplicates.contains(&quoted) {
+            duplicates.push(quoted);
+        }
+    }
+
+    duplicates
+}
+
+/// A person or team that items are assigned to, written either as a bare name or as an object
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(from = "ResourceDef")]
+pub struct ResourceData {
+    pub name: String,
+    /// Any CSS color, otherwise one is generated
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    #[serde(rename = "workWeek", skip_serializing_if = "Option::is_none")]
+    pub work_week: Option<Vec<Weekday>>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub vacations: Vec<HolidayData>,
+    /// Percentage of each working day the resource spends on the chart items
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub availability: Option<u32>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ResourceDef {
+    Name(String),
+    Resource {
+        name: String,
+        color: Option<String>,
+        #[serde(rename = "workWeek")]
+        work_week: Option<Vec<Weekday>>,
+        #[serde(default)]
+        vacations: Vec<HolidayData>,
+        availability: Option<u32>,
+    },
+}
+
+impl From<ResourceDef> for ResourceData {
+    fn from(def: ResourceDef) -> Self {
+        match def {
+            ResourceDef::Name(name) => ResourceData {
+                name,
+                color: None,
+                work_week: None,
+                vacations: vec![],
+                availability: None,
+            },
+            ResourceDef::Resource {
+                name,
+                color,
+                work_week,
+                vacations,
+                availability,
+            } => ResourceData {
+                name,
+                color,
+                work_week,
+                vacations,
+                availability,
+            },
+        }
+    }
+}
+
+/// A non-working day or an inclusive range of them, written either as a bare date or as an object
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(from = "HolidayDef")]
+pub struct HolidayData {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(rename = "startDate")]
+    pub start_date: NaiveDate,
+    #[serde(rename = "endDate")]
+    pub end_date: NaiveDate,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum HolidayDef {
+    Date(NaiveDate),
+    Range {
+        title: Option<String>,
+        #[serde(rename = "startDate")]
+        start_date: NaiveDate,
+        #[serde(rename = "endDate")]
+        end_date: Option<NaiveDate>,
+    },
+}
+
+impl From<HolidayDef> for HolidayData {
+    fn from(def: HolidayDef) -> Self {
+        match def {
+            HolidayDef::Date(date) => HolidayData {
+                title: None,
+                start_date: date,
+                end_date: date,
+            },
+            HolidayDef::Range {
+                title,
+                start_date,
+                end_date,
+            } => HolidayData {
+                title,
+                start_date,
+                end_date: end_date.unwrap_or(start_date),
+            },
+        }
+    }
+}
+
+impl HolidayData {
+    pub fn dates(&self) -> impl Iterator<Item = NaiveDate> {
+        let end_date = self.end_date;
+
+        self.start_date
+            .iter_days()
+            .take_while(move |date| *date <= end_date)
+    }
 }
 
+/// Space around the edges of part of the chart
 #[derive(Debug)]
 pub struct Gutter {
-    left: f32,
-    top: f32,
-    right: f32,
-    bottom: f32,
+    pub left: f32,
+    pub top: f32,
+    pub right: f32,
+    pub bottom: f32,
 }
 
 impl Gutter {
@@ -120,223 +649,1587 @@ impl Gutter {
     }
 }
 
+/// How to lay out the chart
+#[derive(Debug, Clone, Copy)]
+pub struct ChartOptions<'a> {
+    pub title_width: f32,
+    pub max_month_width: f32,
+    pub critical_path: bool,
+    pub shade_weekends: bool,
+    /// The name of the baseline to compare against, otherwise the last one
+    pub baseline: Option<&'a str>,
+    pub time_scale: TimeScale,
+    /// Show the chart from this date instead of from the start of the first item
+    pub from: Option<NaiveDate>,
+    /// Show the chart up to this date instead of to the end of the last item
+    pub to: Option<NaiveDate>,
+}
+
+/// How much time each column of the chart covers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum TimeScale {
+    Day,
+    Week,
+    #[default]
+    Month,
+    Quarter,
+    Year,
+    /// Whichever suits the length of the chart
+    Auto,
+}
+
+impl TimeScale {
+    // A scale that gives a readable number of columns for a number of days
+    fn for_days(days: i64) -> TimeScale {
+        if days <= 45 {
+            TimeScale::Day
+        } else if days <= 200 {
+            TimeScale::Week
+        } else if days <= 1100 {
+            TimeScale::Month
+        } else if days <= 3000 {
+            TimeScale::Quarter
+        } else {
+            TimeScale::Year
+        }
+    }
+
+    // The first day of the period containing a date. Weeks start on Monday.
+    fn start_of(&self, date: NaiveDate, fiscal_year: &FiscalYearData) -> NaiveDate {
+        let months = match self {
+            TimeScale::Day | TimeScale::Auto => return date,
+            TimeScale::Week => {
+                return date - Duration::days(date.weekday().num_days_from_monday() as i64)
+            }
+            TimeScale::Month => 1,
+            TimeScale::Quarter => 3,
+            TimeScale::Year => 12,
+        };
+        let month = month_index(date);
+
+        month_start(month - (month - fiscal_year.start_month as i32 + 1).rem_euclid(months))
+    }
+
+    // The first day of the period after the one containing a date
+    fn next(&self, date: NaiveDate, fiscal_year: &FiscalYearData) -> NaiveDate {
+        let date = self.start_of(date, fiscal_year);
+        let months = match self {
+            TimeScale::Day | TimeScale::Auto => return date.succ(),
+            TimeScale::Week => return date + Duration::days(7),
+            TimeScale::Month => 1,
+            TimeScale::Quarter => 3,
+            TimeScale::Year => 12,
+        };
+
+        month_start(month_index(date) + months)
+    }
+
+    // The days in the longest period, which gets the full column width
+    fn max_days(&self) -> f32 {
+        match self {
+            TimeScale::Day | TimeScale::Auto => 1.0,
+            TimeScale::Week => 7.0,
+            TimeScale::Month => 31.0,
+            TimeScale::Quarter => 92.0,
+            TimeScale::Year => 366.0,
+        }
+    }
+
+    // The heading of the period starting on a date
+    fn label(&self, date: NaiveDate, fiscal_year: &FiscalYearData) -> String {
+        match self {
+            TimeScale::Day | TimeScale::Week | TimeScale::Auto => date.day().to_string(),
+            TimeScale::Month => MONTH_NAMES[date.month0() as usize].to_owned(),
+            TimeScale::Quarter => fiscal_year.format(&fiscal_year.quarter_format, date),
+            TimeScale::Year => fiscal_year.format(&fiscal_year.year_format, date),
+        }
+    }
+
+    // The next longer period to use for columns
+    fn coarser(&self) -> Option<TimeScale> {
+        match self {
+            TimeScale::Day => Some(TimeScale::Week),
+            TimeScale::Week => Some(TimeScale::Month),
+            TimeScale::Month => Some(TimeScale::Quarter),
+            TimeScale::Quarter => Some(TimeScale::Year),
+            TimeScale::Year | TimeScale::Auto => None,
+        }
+    }
+
+    // The longer periods that group the columns in the headings, if they need them
+    fn group(&self) -> Option<TimeScale> {
+        match self {
+            TimeScale::Day | TimeScale::Week => Some(TimeScale::Month),
+            TimeScale::Month | TimeScale::Quarter => Some(TimeScale::Year),
+            _ => None,
+        }
+    }
+}
+
+/// Where everything in the chart goes, in pixels
 #[derive(Debug)]
-struct RenderData {
-    title: String,
-    gutter: Gutter,
-    row_gutter: Gutter,
-    row_height: f32,
-    resource_gutter: Gutter,
-    resource_height: f32,
-    marked_date_offset: Option<f32>,
-    title_width: f32,
-    max_month_width: f32,
-    rect_corner_radius: f32,
-    styles: Vec<String>,
-    cols: Vec<ColumnRenderData>,
-    rows: Vec<RowRenderData>,
-    resources: Vec<String>,
+pub struct RenderData {
+    pub title: String,
+    pub gutter: Gutter,
+    pub row_gutter: Gutter,
+    pub row_height: f32,
+    pub resource_gutter: Gutter,
+    pub resource_height: f32,
+    pub marked_date_offset: Option<f32>,
+    pub title_width: f32,
+    /// Indent of the titles of nested items
+    pub indent_width: f32,
+    pub max_month_width: f32,
+    pub rect_corner_radius: f32,
+    pub styles: Vec<String>,
+    /// How much time each column covers, never auto
+    pub time_scale: TimeScale,
+    pub cols: Vec<ColumnRenderData>,
+    /// The longer periods grouping the columns in an upper row of headings, if there is one
+    pub groups: Vec<ColumnRenderData>,
+    pub rows: Vec<RowRenderData>,
+    pub links: Vec<LinkRenderData>,
+    pub holidays: Vec<ShadingRenderData>,
+    pub resources: Vec<String>,
+    /// The CSS color of each resource
+    pub resource_colors: Vec<String>,
+    /// Days of work assigned to each resource
+    pub resource_days: Vec<f32>,
+    /// The percentage of the whole chart that is complete, if any item has a progress
+    pub progress: Option<u32>,
+}
+
+/// An item in the chart
+#[derive(Debug)]
+pub struct RowRenderData {
+    pub title: String,
+    /// How deeply the item is nested inside summary items
+    pub depth: usize,
+    pub parent: Option<usize>,
+    pub summary: bool,
+    pub assignments: Vec<Assignment>,
+    /// Non-working days of the resource
+    pub weekends: Vec<ShadingRenderData>,
+    pub vacations: Vec<ShadingRenderData>,
+    pub offset: f32,
+    /// If length not present then this is a milestone
+    pub length: Option<f32>,
+    pub open: bool,
+    /// The percentage complete to show, if any
+    pub progress: Option<u32>,
+    pub critical: bool,
+    /// Where the item was in the baseline, and how many days later it now ends
+    pub baseline: Option<BaselineRenderData>,
+    pub slip: Option<i64>,
+    /// Whether the item starts before the chart does, or ends after it, and is cut off
+    pub clipped_start: bool,
+    pub clipped_end: bool,
+}
+
+/// Where an item was in the baseline
+#[derive(Debug)]
+pub struct BaselineRenderData {
+    pub offset: f32,
+    /// If length not present then this was a milestone
+    pub length: Option<f32>,
+}
+
+/// A shaded run of days
+#[derive(Debug)]
+pub struct ShadingRenderData {
+    pub offset: f32,
+    pub width: f32,
+}
+
+/// A dependency connector between two rows
+#[derive(Debug)]
+pub struct LinkRenderData {
+    /// Indices of the predecessor and successor rows
+    pub from: usize,
+    pub to: usize,
+    pub kind: DependencyKind,
+}
+
+/// A month in the chart
+#[derive(Debug)]
+pub struct ColumnRenderData {
+    pub width: f32,
+    pub label: String,
+}
+
+#[derive(Debug, Clone)]
+struct ItemSchedule {
+    start_date: NaiveDate,
+    // Items without resources use the ones from the previous item. The first is the lead
+    // resource, whose calendar the item follows.
+    assignments: Vec<Assignment>,
+    // The real number of days the item spans, including weekends. None for milestones.
+    shadow_duration: Option<i64>,
+    // The latest the item can start without delaying the end of the project
+    late_start_date: NaiveDate,
+}
+
+impl ItemSchedule {
+    fn lead_resource_index(&self) -> usize {
+        self.assignments[0].resource_index
+    }
+
+    fn end_date(&self) -> NaiveDate {
+        self.start_date + Duration::days(self.shadow_duration.unwrap_or(0))
+    }
+
+    fn total_float(&self) -> i64 {
+        (self.late_start_date - self.start_date).num_days()
+    }
+}
+
+// An item along with its place in the hierarchy of items
+#[derive(Debug)]
+struct OutlineItem<'a> {
+    item: &'a ItemData,
+    depth: usize,
+    parent: Option<usize>,
+    children: Vec<usize>,
+}
+
+impl OutlineItem<'_> {
+    fn is_summary(&self) -> bool {
+        !self.children.is_empty()
+    }
+}
+
+/// The resolved dates of every item in a chart
+#[derive(Debug, Clone)]
+pub struct Schedule {
+    /// Every item in chart order, with child items after their parent
+    pub items: Vec<ScheduledItem>,
+    pub start_date: NaiveDate,
+    /// The first working day after the last item finishes
+    pub end_date: NaiveDate,
+    /// Percentage of the chart that is complete, weighted by item duration
+    pub progress: u32,
 }
 
-#[derive(Debug)]
-struct RowRenderData {
-    title: String,
-    resource_index: usize,
-    offset: f32,
-    // If length not present then this is a milestone
-    length: Option<f32>,
-    open: bool,
-}
+/// The resolved dates of an item
+#[derive(Debug, Clone)]
+pub struct ScheduledItem {
+    pub id: Option<String>,
+    pub title: String,
+    /// How deeply the item is nested inside summary items
+    pub depth: usize,
+    /// The index of the summary item containing this one
+    pub parent: Option<usize>,
+    pub summary: bool,
+    pub milestone: bool,
+    pub start_date: NaiveDate,
+    /// The first working day after the item finishes, which is when a following item can start
+    pub end_date: NaiveDate,
+    /// The number of working days from the start date up to the end date
+    pub working_days: i64,
+    /// The resources working on the item, led by the first one
+    pub assignments: Vec<Assignment>,
+    /// The latest the item can start without delaying the end of the chart
+    pub late_start_date: NaiveDate,
+    /// Days the item can slip without delaying the end of the chart
+    pub total_float: i64,
+    /// Percentage complete, weighted by duration for summary items
+    pub progress: u32,
+}
+
+impl ScheduledItem {
+    /// The latest the item can end without delaying the end of the chart
+    pub fn late_end_date(&self) -> NaiveDate {
+        self.late_start_date + (self.end_date - self.start_date)
+    }
+
+    /// Whether the item is on the critical path
+    pub fn is_critical(&self) -> bool {
+        self.total_float <= 0
+    }
+}
+
+impl Schedule {
+    /// Snapshot the dates of every item as a baseline, failing if items share a name
+    pub fn baseline(&self, name: &str) -> Result<BaselineData, Box<dyn Error>> {
+        let items: Vec<BaselineItemData> = self
+            .items
+            .iter()
+            .map(|item| BaselineItemData {
+                id: item.id.clone().unwrap_or_else(|| item.title.clone()),
+                start_date: item.start_date,
+                end_date: item.end_date,
+            })
+            .collect();
+        let duplicates = duplicate_names(items.iter().map(|item| item.id.as_str()));
+
+        if !duplicates.is_empty() {
+            return Err(From::from(format!(
+                "More than one item is named {}, so baseline '{}' cannot be saved. Give them each \
+                 a unique id",
+                duplicates.join(", "),
+                name
+            )));
+        }
+
+        Ok(BaselineData {
+            name: name.to_owned(),
+            items,
+        })
+    }
+}
+
+// Part of a resource reserved for an item while leveling
+#[derive(Debug, Clone, Copy)]
+struct Booking {
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+    allocation: u32,
+}
+
+impl<'a> GanttChartTool<'a> {
+    pub fn new(log: &'a dyn GanttChartLog) -> GanttChartTool<'a> {
+        GanttChartTool {
+            log,
+            today: Local::now().naive_local().date(),
+        }
+    }
+
+    /// Use this date for today in relative dates instead of the current date
+    pub fn with_today(mut self, today: NaiveDate) -> GanttChartTool<'a> {
+        self.today = today;
+        self
+    }
+
+    pub fn run(
+        &mut self,
+        args: impl IntoIterator<Item = std::ffi::OsString>,
+    ) -> Result<(), Box<dyn Error>> {
+        let cli = match Cli::try_parse_from(args) {
+            Ok(cli) => cli,
+            Err(err) => {
+                output!(self.log, "{}", err.to_string());
+                return Ok(());
+            }
+        };
+
+        if let Some(Command::Baseline {
+            command:
+                BaselineCommand::Save {
+                    ref name,
+                    ref input_file,
+                    ref output_file,
+                    level,
+                    ref holidays_file,
+                    today,
+                },
+        }) = cli.command
+        {
+            if let Some(today) = today {
+                self.today = today;
+            }
+
+            return self.save_baseline(
+                name,
+                input_file,
+                output_file.as_deref(),
+                level,
+                holidays_file.as_deref(),
+            );
+        }
+
+        if let Some(today) = cli.today {
+            self.today = today;
+        }
+
+        let mut chart_data = Self::read_chart_file(cli.get_input()?)?;
+
+        if let Some(reader) = cli.get_holidays_input()? {
+            chart_data
+                .holidays
+                .append(&mut Self::read_holidays_file(reader)?);
+        }
+
+        if let Some(mut reader) = cli.get_baseline_input()? {
+            let mut content = String::new();
+
+            reader.read_to_string(&mut content)?;
+
+            let mut baseline_file: BaselineFileData = json5::from_str(&content)?;
+
+            chart_data.baselines.append(&mut baseline_file.baselines);
+        }
+
+        let schedule = self.schedule(&chart_data, cli.level)?;
+        let text_width = match cli.format {
+            OutputFormat::Term => Some(
+                cli.width
+                    .or_else(TermRenderer::terminal_width)
+                    .unwrap_or(80),
+            ),
+            OutputFormat::Ascii => Some(cli.width.unwrap_or(80)),
+            _ => None,
+        };
+        let mut options = ChartOptions {
+            title_width: cli.title_width,
+            max_month_width: cli.max_month_width,
+            critical_path: cli.critical_path,
+            shade_weekends: cli.shade_weekends,
+            baseline: cli.baseline.as_deref(),
+            time_scale: cli.time_scale.unwrap_or_default(),
+            from: cli.from,
+            to: cli.to,
+        };
+        let mut render_data = self.layout(&options, &chart_data, &schedule)?;
+
+        // Unless a time scale is given, use longer periods when text columns are too narrow
+        if let (Some(width), None) = (text_width, cli.time_scale) {
+            while !renderer::text_columns_fit(&render_data, width) {
+                match render_data.time_scale.coarser() {
+                    Some(time_scale) => {
+                        options.time_scale = time_scale;
+                        render_data = self.layout(&options, &chart_data, &schedule)?;
+                    }
+                    None => break,
+                }
+            }
+        }
+
+        let background = |default: Option<&str>| match cli.background.as_deref().or(default) {
+            Some("none") | None => None,
+            Some(color) => Some(color.to_owned()),
+        };
+        let renderer: Box<dyn Renderer> = match cli.format {
+            OutputFormat::Svg => Box::new(SvgRenderer {
+                add_resource_table: cli.add_resource_table,
+                background: background(None),
+            }),
+            OutputFormat::Html => Box::new(HtmlRenderer {
+                add_resource_table: cli.add_resource_table,
+            }),
+            #[cfg(feature = "png")]
+            OutputFormat::Png => Box::new(PngRenderer {
+                add_resource_table: cli.add_resource_table,
+                // SVG pixels are 96 to the inch
+                scale: cli.dpi.map_or(cli.scale, |dpi| dpi / 96.0),
+                background: background(Some("white")),
+            }),
+            #[cfg(not(feature = "png"))]
+            OutputFormat::Png => {
+                bail!("PNG output needs gantt-chart to be built with the png feature")
+            }
+            #[cfg(feature = "pdf")]
+            OutputFormat::Pdf => Box::new(PdfRenderer {
+                add_resource_table: cli.add_resource_table,
+                paper: cli.paper,
+                orientation: cli.orientation,
+            }),
+            #[cfg(not(feature = "pdf"))]
+            OutputFormat::Pdf => {
+                bail!("PDF output needs gantt-chart to be built with the pdf feature")
+            }
+            OutputFormat::Term => Box::new(TermRenderer {
+                add_resource_table: cli.add_resource_table,
+                width: text_width.unwrap_or(80),
+            }),
+            OutputFormat::Ascii => Box::new(AsciiRenderer {
+                add_resource_table: cli.add_resource_table,
+                width: text_width.unwrap_or(80),
+            }),
+        };
+
+        renderer.render(&schedule, &render_data, &mut cli.get_output()?)?;
+
+        Ok(())
+    }
+
+    // Schedule the chart and add the dates to the chart file, or a separate baseline file
+    fn save_baseline(
+        &self,
+        name: &str,
+        input_file: &Path,
+        output_file: Option<&Path>,
+        level: bool,
+        holidays_file: Option<&Path>,
+    ) -> Result<(), Box<dyn Error>> {
+        let read_file = |path: &Path| {
+            std::fs::read_to_string(path)
+                .context(format!("Unable to open file '{}'", path.to_string_lossy()))
+        };
+        let content = read_file(input_file)?;
+        let mut chart_data: ChartData = json5::from_str(&content)?;
+
+        if let Some(path) = holidays_file {
+            chart_data
+                .holidays
+                .append(&mut Self::read_holidays_file(Box::new(io::Cursor::new(
+                    read_file(path)?,
+                )))?);
+        }
+
+        let baseline = self.schedule(&chart_data, level)?.baseline(name)?;
+        let (path, content) = match output_file {
+            Some(path) if path.exists() => (path, read_file(path)?),
+            Some(path) => (path, "{}\n".to_owned()),
+            None => (input_file, content),
+        };
+
+        std::fs::write(path, splice_baseline(&content, &baseline)?)
+            .context(format!("Unable to write file '{}'", path.to_string_lossy()))?;
+
+        Ok(())
+    }
+
+    fn read_chart_file(mut reader: Box<dyn Read>) -> Result<ChartData, Box<dyn Error>> {
+        let mut content = String::new();
+
+        reader.read_to_string(&mut content)?;
+
+        let chart_data: ChartData = json5::from_str(&content)?;
+
+        Ok(chart_data)
+    }
+
+    fn read_holidays_file(mut reader: Box<dyn Read>) -> Result<Vec<HolidayData>, Box<dyn Error>> {
+        let mut content = String::new();
+
+        reader.read_to_string(&mut content)?;
+
+        if content.trim_start().starts_with("BEGIN:VCALENDAR") {
+            return Self::parse_icalendar(&content);
+        }
+
+        // One `YYYY-MM-DD` or `YYYY-MM-DD..YYYY-MM-DD` per line, optionally followed by a title
+        let mut holidays = vec![];
+
+        for (i, line) in content.lines().enumerate() {
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (dates, title) = match line.split_once(char::is_whitespace) {
+                Some((dates, title)) => (dates, Some(title.trim().to_owned())),
+                None => (line, None),
+            };
+            let (start, end) = dates.split_once("..").unwrap_or((dates, dates));
+            let parse_date = |s: &str| {
+                NaiveDate::parse_from_str(s, "%Y-%m-%d").context(format!(
+                    "Invalid holiday date '{}' on line {}",
+                    s,
+                    i + 1
+                ))
+            };
+
+            holidays.push(HolidayData {
+                title,
+                start_date: parse_date(start)?,
+                end_date: parse_date(end)?,
+            });
+        }
+
+        Ok(holidays)
+    }
+
+    fn parse_icalendar(content: &str) -> Result<Vec<HolidayData>, Box<dyn Error>> {
+        let mut holidays = vec![];
+        let mut lines: Vec<String> = vec![];
+
+        // Undo the line folding, where long lines continue on lines starting with whitespace
+        for line in content.lines() {
+            match lines.last_mut() {
+                Some(last) if line.starts_with(' ') || line.starts_with('\t') => {
+                    last.push_str(&line[1..])
+                }
+                _ => lines.push(line.to_owned()),
+            }
+        }
+
+        let mut title: Option<String> = None;
+        let mut start_date: Option<NaiveDate> = None;
+        let mut end_date: Option<NaiveDate> = None;
+
+        for line in lines.iter() {
+            let (name, value) = match line.split_once(':') {
+                Some((name, value)) => (name, value.trim()),
+                None => continue,
+            };
+            // Properties can have parameters, e.g. `DTSTART;VALUE=DATE:20261225`
+            let (name, params) = name.split_once(';').unwrap_or((name, ""));
+            let parse_date = |value: &str| {
+                NaiveDate::parse_from_str(value.get(..8).unwrap_or(value), "%Y%m%d")
+                    .context(format!("Invalid iCalendar date '{}'", value))
+            };
+
+            match name {
+                "BEGIN" if value == "VEVENT" => {
+                    title = None;
+                    start_date = None;
+                    end_date = None;
+                }
+                "SUMMARY" => title = Some(value.to_owned()),
+                "DTSTART" => start_date = Some(parse_date(value)?),
+                "DTEND" => {
+                    let date = parse_date(value)?;
+
+                    // The end of an all-day event is the day after it finishes
+                    end_date = Some(if params.contains("VALUE=DATE") || !value.contains('T') {
+                        date.pred()
+                    } else {
+                        date
+                    });
+                }
+                "END" if value == "VEVENT" => {
+                    if let Some(start_date) = start_date {
+                        holidays.push(HolidayData {
+                            title: title.take(),
+                            start_date,
+                            end_date: end_date.unwrap_or(start_date).max(start_date),
+                        });
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        Ok(holidays)
+    }
+
+    fn hsv_to_rgb(h: f32, s: f32, v: f32) -> u32 {
+        let h_i = (h * 6.0) as usize;
+        let f = h * 6.0 - h_i as f32;
+        let p = v * (1.0 - s);
+        let q = v * (1.0 - f * s);
+        let t = v * (1.0 - (1.0 - f) * s);
+
+        fn rgb(r: f32, g: f32, b: f32) -> u32 {
+            ((r * 256.0) as u32) << 16 | ((g * 256.0) as u32) << 8 | ((b * 256.0) as u32)
+        }
+
+        if h_i == 0 {
+            rgb(v, t, p)
+        } else if h_i == 1 {
+            rgb(q, v, p)
+        } else if h_i == 2 {
+            rgb(p, v, t)
+        } else if h_i == 3 {
+            rgb(p, q, v)
+        } else if h_i == 4 {
+            rgb(t, p, v)
+        } else {
+            rgb(v, p, q)
+        }
+    }
+
+    // Every day of the holidays, checking that each range ends after it starts
+    fn holiday_dates(holidays: &[HolidayData]) -> Result<HashSet<NaiveDate>, Box<dyn Error>> {
+        let mut dates = HashSet::new();
+
+        for holiday in holidays.iter() {
+            if holiday.end_date < holiday.start_date {
+                return Err(From::from(format!(
+                    "Holiday ending {} ends before it starts",
+                    holiday.end_date
+                )));
+            }
+
+            dates.extend(holiday.dates());
+        }
+
+        Ok(dates)
+    }
+
+    fn chart_calendar(chart_data: &ChartData) -> Result<Calendar, Box<dyn Error>> {
+        let work_week = chart_data
+            .work_week
+            .as_deref()
+            .unwrap_or(&DEFAULT_WORK_WEEK);
+
+        if work_week.is_empty() {
+            bail!("The chart work week must contain at least one day");
+        }
+
+        Ok(Calendar::new(
+            work_week,
+            Self::holiday_dates(&chart_data.holidays)?,
+        ))
+    }
+
+    // The calendar for each resource, in the same order as the resources
+    fn resource_calendars(chart_data: &ChartData) -> Result<Vec<Calendar>, Box<dyn Error>> {
+        let calendar = Self::chart_calendar(chart_data)?;
+        let mut calendars = vec![];
+
+        for resource in chart_data.resources.iter() {
+            let mut resource_calendar = calendar
+                .clone()
+                .with_vacations(Self::holiday_dates(&resource.vacations)?);
+
+            if let Some(ref work_week) = resource.work_week {
+                if work_week.is_empty() {
+                    return Err(From::from(format!(
+                        "The work week for resource '{}' must contain at least one day",
+                        resource.name
+                    )));
+                }
+
+                resource_calendar = resource_calendar.with_work_week(work_week);
+            }
+
+            if let Some(availability) = resource.availability {
+                if availability == 0 || availability > 100 {
+                    return Err(From::from(format!(
+                        "The availability of resource '{}' must be between 1 and 100",
+                        resource.name
+                    )));
+                }
+
+                resource_calendar = resource_calendar.with_availability(availability);
+            }
+
+            calendars.push(resource_calendar);
+        }
+
+        Ok(calendars)
+    }
+
+    // Flatten the items into the order they appear in the chart, children after their parent
+    fn outline_items(items: &[ItemData]) -> Vec<OutlineItem<'_>> {
+        fn add_items<'a>(
+            items: &'a [ItemData],
+            depth: usize,
+            parent: Option<usize>,
+            outline: &mut Vec<OutlineItem<'a>>,
+        ) {
+            for item in items.iter() {
+                let i = outline.len();
+
+                outline.push(OutlineItem {
+                    item,
+                    depth,
+                    parent,
+                    children: vec![],
+                });
+
+                if let Some(parent) = parent {
+                    outline[parent].children.push(i);
+                }
+
+                add_items(&item.items, depth + 1, Some(i), outline);
+            }
+        }
+
+        let mut outline = vec![];
+
+        add_items(items, 0, None, &mut outline);
+
+        outline
+    }
+
+    fn schedule_items(
+        &self,
+        chart_data: &ChartData,
+        level: bool,
+    ) -> Result<Vec<ItemSchedule>, Box<dyn Error>> {
+        let outline = Self::outline_items(&chart_data.items);
+        let items: Vec<&ItemData> = outline.iter().map(|entry| entry.item).collect();
+        let calendars = Self::resource_calendars(chart_data)?;
+        let mut assignments: Vec<Vec<Assignment>> = Vec::with_capacity(items.len());
+
+        for (i, item) in items.iter().enumerate() {
+            if outline[i].is_summary() && (item.duration.is_some() || item.progress.is_some()) {
+                return Err(From::from(format!(
+                    "Item '{}' has child items so cannot have a duration or progress",
+                    item.name()
+                )));
+            }
+
+            if item.progress.unwrap_or(0) > 100 {
+                return Err(From::from(format!(
+                    "The progress of item '{}' must be between 0 and 100",
+                    item.name()
+                )));
+            }
+
+            if !item.assignments.is_empty() {
+                for assignment in item.assignments.iter() {
+                    if assignment.resource_index >= chart_data.resources.len() {
+                        return Err(From::from("Resource index is out of range"));
+                    }
+
+                    if assignment.allocation == 0 || assignment.allocation > 100 {
+                        return Err(From::from(format!(
+                            "The allocation of item '{}' must be between 1 and 100",
+                            item.name()
+                        )));
+                    }
+                }
+
+                assignments.push(item.assignments.clone());
+            } else if i > 0 && !assignments[i - 1].is_empty() {
+                assignments.push(assignments[i - 1].clone());
+            } else if outline[i].is_summary() {
+                assignments.push(vec![]);
+            } else {
+                return Err(From::from("First item must contain a resource index"));
+            }
+        }
+
+        let lead_resource_index = |i: usize| assignments[i][0].resource_index;
+
+        let mut ids: HashMap<&str, usize> = HashMap::new();
+
+        for (i, item) in items.iter().enumerate() {
+            if let Some(ref id) = item.id {
+                if ids.insert(id.as_str(), i).is_some() {
+                    return Err(From::from(format!(
+                        "Item id '{}' is used more than once",
+                        id
+                    )));
+                }
+            }
+        }
+
+        // The start dates and dependencies of summary items apply to all the items inside them.
+        // Items with neither a start date nor dependencies of their own follow the previous item.
+        let mut predecessors: Vec<Vec<(usize, DependencyKind, i64)>> =
+            Vec::with_capacity(items.len());
+        let mut start_dates: Vec<Option<NaiveDate>> = Vec::with_capacity(items.len());
+        let mut last_item: Option<usize> = None;
+        // The item before each item, for start dates relative to the previous item
+        let mut previous_items: Vec<Option<usize>> = Vec::with_capacity(items.len());
+
+        for (i, item) in items.iter().enumerate() {
+            let mut links = vec![];
+            let mut start_date = None;
+
+            previous_items.push(last_item);
+
+            if outline[i].is_summary() {
+                predecessors.push(links);
+                start_dates.push(start_date);
+                continue;
+            }
+
+            let mut ancestor = Some(i);
+
+            while let Some(j) = ancestor {
+                let check_lag = |lag: i64| {
+                    if lag.unsigned_abs() > MAX_LAG_DAYS {
+                        Err(format!(
+                            "Item '{}' has a lag of {} days, which is longer than the {} days allowed",
+                            items[j].name(),
+                            lag,
+                            MAX_LAG_DAYS
+                        ))
+                    } else {
+                        Ok(lag)
+                    }
+                };
+
+                for dependency in items[j].depends_on.iter() {
+                    match ids.get(dependency.id.as_str()) {
+                        Some(&k) => links.push((k, dependency.kind, check_lag(dependency.lag)?)),
+                        None => {
+                            return Err(From::from(format!(
+                                "Item '{}' depends on unknown item '{}'",
+                                items[j].name(),
+                                dependency.id
+                            )))
+                        }
+                    }
+                }
+
+                if let Some(ref expr) = items[j].start_date {
+                    match (expr.previous_item_link(), previous_items[j]) {
+                        (Some((kind, lag)), Some(k)) => links.push((k, kind, check_lag(lag)?)),
+                        (Some(_), None) => {
+                            return Err(From::from(format!(
+                                "Item '{}' starts relative to the previous item, but there is none",
+                                items[j].name()
+                            )))
+                        }
+                        (None, _) => start_date = start_date.max(expr.resolve(self.today)?),
+                    }
+                }
+
+                ancestor = outline[j].parent;
+            }
+
+            if item.depends_on.is_empty() && item.start_date.is_none() {
+                match last_item {
+                    Some(j) => links.push((j, DependencyKind::FinishToStart, 0)),
+                    None if links.is_empty() && start_date.is_none() => {
+                        bail!("First item must contain a start date or dependencies")
+                    }
+                    None => (),
+                }
+            }
+
+            predecessors.push(links);
+            start_dates.push(start_date);
+            last_item = Some(i);
+        }
+
+        // Order the items so that every item comes after all of its predecessors, and summary
+        // items come after all of their children
+        let mut successors: Vec<Vec<usize>> = vec![vec![]; items.len()];
+        let mut num_unscheduled: Vec<usize> = vec![0; items.len()];
+
+        for (i, links) in predecessors.iter().enumerate() {
+            for j in links
+                .iter()
+                .map(|&(j, _, _)| j)
+                .chain(outline[i].children.iter().cloned())
+            {
+                successors[j].push(i);
+                num_unscheduled[i] += 1;
+            }
+        }
+
+        let mut ready: VecDeque<usize> = (0..items.len())
+            .filter(|&i| num_unscheduled[i] == 0)
+            .collect();
+        let mut order = Vec::with_capacity(items.len());
+
+        while let Some(j) = ready.pop_front() {
+            order.push(j);
+
+            for &i in successors[j].iter() {
+                num_unscheduled[i] -= 1;
+
+                if num_unscheduled[i] == 0 {
+                    ready.push_back(i);
+                }
+            }
+        }
+
+        if order.len() < items.len() {
+            // Every item left over has a predecessor that is also left over, so walking
+            // backwards through them must eventually revisit an item
+            let mut path: Vec<usize> = vec![];
+            let mut i = (0..items.len())
+                .find(|&i| num_unscheduled[i] > 0)
+                .unwrap_or_default();
+
+            while !path.contains(&i) {
+                path.push(i);
+                i = predecessors[i]
+                    .iter()
+                    .map(|&(j, _, _)| j)
+                    .chain(outline[i].children.iter().cloned())
+                    .find(|&j| num_unscheduled[j] > 0)
+                    .unwrap_or(i);
+            }
+
+            let first = path.iter().position(|&j| j == i).unwrap_or_default();
+            let mut cycle: Vec<String> = path[first..]
+                .iter()
+                .rev()
+                .map(|&j| format!("'{}'", items[j].name()))
+                .collect();
+
+            cycle.push(cycle[0].clone());
+
+            return Err(From::from(format!(
+                "Dependency cycle detected between items {}",
+                cycle.join(" -> ")
+            )));
+        }
+
+        let unit_of = |i: usize| {
+            items[i]
+                .duration_unit
+                .or(chart_data.duration_unit)
+                .unwrap_or_default()
+        };
+        let earliest_start_date = |i: usize, schedules: &[Option<ItemSchedule>]| {
+            if outline[i].is_summary() {
+                return outline[i]
+                    .children
+                    .iter()
+                    .filter_map(|&j| schedules[j].as_ref().map(|schedule| schedule.start_date))
+                    .min()
+                    .unwrap_or(NaiveDate::MIN);
+            }
+
+            let item = &items[i];
+            let item_days = item.duration.unwrap_or(0);
+            let calendar = &calendars[lead_resource_index(i)];
+            let mut start_date = start_dates[i];
+
+            for &(j, kind, lag) in predecessors[i].iter() {
+                let predecessor = match schedules[j] {
+                    Some(ref schedule) => schedule,
+                    None => continue,
+                };
+                let lag = Duration::days(lag);
+                let earliest_date = match kind {
+                    DependencyKind::FinishToStart => predecessor.end_date() + lag,
+                    DependencyKind::StartToStart => predecessor.start_date + lag,
+                    DependencyKind::FinishToFinish => {
+                        calendar.start_date_for(predecessor.end_date() + lag, item_days, unit_of(i))
+                    }
+                    DependencyKind::StartToFinish => {
+                        calendar.start_date_for(predecessor.start_date + lag, item_days, unit_of(i))
+                    }
+                };
+
+                start_date = Some(start_date.map_or(earliest_date, |date| date.max(earliest_date)));
+            }
+
+            let start_date = start_date.unwrap_or(NaiveDate::MIN);
+
+            // Work cannot start on a weekend or holiday unless the start date says so
+            if start_dates[i].is_none() || !predecessors[i].is_empty() {
+                calendar.next_working_day(start_date)
+            } else {
+                start_date
+            }
+        };
+
+        // Schedule the items as their predecessors complete, highest priority first, so that
+        // when leveling the more important items book their resource first
+        let mut schedules: Vec<Option<ItemSchedule>> = vec![None; items.len()];
+        let mut bookings: Vec<Vec<Booking>> = vec![vec![]; chart_data.resources.len()];
+        let mut num_unscheduled: Vec<usize> = (0..items.len())
+            .map(|i| predecessors[i].len() + outline[i].children.len())
+            .collect();
+        let mut ready = BinaryHeap::new();
+
+        for i in (0..items.len()).filter(|&i| num_unscheduled[i] == 0) {
+            ready.push(Reverse((
+                Reverse(items[i].priority.unwrap_or(0)),
+                earliest_start_date(i, &schedules),
+                i,
+            )));
+        }
+
+        while let Some(Reverse((_, mut start_date, i))) = ready.pop() {
+            let item = &items[i];
+
+            if outline[i].is_summary() {
+                let end_date = outline[i]
+                    .children
+                    .iter()
+                    .filter_map(|&j| schedules[j].as_ref().map(|schedule| schedule.end_date()))
+                    .max()
+                    .unwrap_or(start_date);
+
+                schedules[i] = Some(ItemSchedule {
+                    start_date,
+                    assignments: vec![],
+                    shadow_duration: Some((end_date - start_date).num_days()),
+                    late_start_date: start_date,
+                });
+
+                for &k in successors[i].iter() {
+                    num_unscheduled[k] -= 1;
+
+                    if num_unscheduled[k] == 0 {
+                        ready.push(Reverse((
+                            Reverse(items[k].priority.unwrap_or(0)),
+                            earliest_start_date(k, &schedules),
+                            k,
+                        )));
+                    }
+                }
+
+                continue;
+            }
+
+            let calendar = &calendars[lead_resource_index(i)];
+            let elapsed_days = |start_date| {
+                item.duration
+                    .map(|item_days| calendar.elapsed_days(start_date, item_days, unit_of(i)))
+            };
+
+            if level && elapsed_days(start_date).unwrap_or(0) > 0 {
+                // Keep moving the item until none of its resources would be over-allocated,
+                // trying again each time some of the work already booked finishes
+                loop {
+                    let end_date =
+                        start_date + Duration::days(elapsed_days(start_date).unwrap_or(0));
+                    let mut next_start_date: Option<NaiveDate> = None;
+
+                    for assignment in assignments[i].iter() {
+                        let booked = &bookings[assignment.resource_index];
+
+                        if Self::peak_load(booked, start_date, end_date) + assignment.allocation
+                            > 100
+                        {
+                            let booked_end_date = booked
+                                .iter()
+                                .filter(|booking| {
+                                    booking.start_date < end_date && start_date < booking.end_date
+                                })
+                                .map(|booking| booking.end_date)
+                                .min();
+
+                            next_start_date =
+                                next_start_date.min(booked_end_date).or(booked_end_date);
+                        }
+                    }
+
+                    match next_start_date {
+                        Some(date) => start_date = calendar.next_working_day(date),
+                        None => break,
+                    }
+                }
+
+                let end_date = start_date + Duration::days(elapsed_days(start_date).unwrap_or(0));
+
+                for assignment in assignments[i].iter() {
+                    bookings[assignment.resource_index].push(Booking {
+                        start_date,
+                        end_date,
+                        allocation: assignment.allocation,
+                    });
+                }
+            }
+
+            schedules[i] = Some(ItemSchedule {
+                start_date,
+                assignments: assignments[i].clone(),
+                shadow_duration: elapsed_days(start_date),
+                late_start_date: start_date,
+            });
+
+            for &k in successors[i].iter() {
+                num_unscheduled[k] -= 1;
+
+                if num_unscheduled[k] == 0 {
+                    ready.push(Reverse((
+                        Reverse(items[k].priority.unwrap_or(0)),
+                        earliest_start_date(k, &schedules),
+                        k,
+                    )));
+                }
+            }
+        }
+
+        let mut schedules: Vec<ItemSchedule> = schedules.into_iter().flatten().collect();
+
+        // Work backwards from the end of the project to find the latest each item can finish
+        let project_end_date = schedules
+            .iter()
+            .map(|schedule| schedule.end_date())
+            .max()
+            .unwrap_or(NaiveDate::MIN);
+        let mut late_end_dates = vec![project_end_date; items.len()];
+
+        for &i in order.iter().rev() {
+            let item_days = Duration::days(schedules[i].shadow_duration.unwrap_or(0));
+            let late_start_date = late_end_dates[i] - item_days;
+
+            schedules[i].late_start_date = late_start_date;
+
+            for &j in outline[i].children.iter() {
+                late_end_dates[j] = late_end_dates[j].min(late_end_dates[i]);
+            }
+
+            for &(j, kind, lag) in predecessors[i].iter() {
+                let lag = Duration::days(lag);
+                let predecessor_days = Duration::days(schedules[j].shadow_duration.unwrap_or(0));
+                let latest_end_date = match kind {
+                    DependencyKind::FinishToStart => late_start_date - lag,
+                    DependencyKind::StartToStart => late_start_date - lag + predecessor_days,
+                    DependencyKind::FinishToFinish => late_end_dates[i] - lag,
+                    DependencyKind::StartToFinish => late_end_dates[i] - lag + predecessor_days,
+                };
+
+                late_end_dates[j] = late_end_dates[j].min(latest_end_date);
+            }
+        }
+
+        Ok(schedules)
+    }
+
+    // The highest total allocation of the bookings at any time between two dates
+    fn peak_load(bookings: &[Booking], start_date: NaiveDate, end_date: NaiveDate) -> u32 {
+        // The load only goes up where a booking starts
+        std::iter::once(start_date)
+            .chain(
+                bookings
+                    .iter()
+                    .map(|booking| booking.start_date)
+                    .filter(|date| start_date < *date && *date < end_date),
+            )
+            .map(|date| {
+                bookings
+                    .iter()
+                    .filter(|booking| booking.start_date <= date && date < booking.end_date)
+                    .map(|booking| booking.allocation)
+                    .sum::<u32>()
+            })
+            .max()
+            .unwrap_or(0)
+    }
+
+    // Warn about resources that are assigned to more than 100% of their time
+    fn check_allocations(&self, chart_data: &ChartData, schedules: &[ItemSchedule]) {
+        let outline = Self::outline_items(&chart_data.items);
+
+        for (resource_index, resource) in chart_data.resources.iter().enumerate() {
+            let mut assigned: Vec<(usize, u32)> = vec![];
+            let mut dates: Vec<NaiveDate> = vec![];
+
+            for (i, schedule) in schedules.iter().enumerate() {
+                if schedule.shadow_duration.unwrap_or(0) == 0 {
+                    continue;
+                }
+
+                for assignment in schedule.assignments.iter() {
+                    if assignment.resource_index == resource_index {
+                        assigned.push((i, assignment.allocation));
+                        dates.push(schedule.start_date);
+                        dates.push(schedule.end_date());
+                    }
+                }
+            }
+
+            dates.sort();
+            dates.dedup();
+
+            // Find the spans between consecutive start and end dates where the resource is
+            // over-allocated, merging neighbouring spans with the same items
+            let mut overloads: Vec<(NaiveDate, NaiveDate, u32, Vec<usize>)> = vec![];
+
+            for span in dates.windows(2) {
+                let active: Vec<(usize, u32)> = assigned
+                    .iter()
+                    .filter(|(i, _)| {
+                        schedules[*i].start_date <= span[0] && span[0] < schedules[*i].end_date()
+                    })
+                    .cloned()
+                    .collect();
+                let load: u32 = active.iter().map(|(_, allocation)| allocation).sum();
+                let items: Vec<usize> = active.iter().map(|(i, _)| *i).collect();
+
+                if load <= 100 {
+                    continue;
+                }
 
-#[derive(Debug)]
-struct ColumnRenderData {
-    width: f32,
-    month_name: String,
-}
+                match overloads.last_mut() {
+                    Some(overload) if overload.1 == span[0] && overload.3 == items => {
+                        overload.1 = span[1];
+                        overload.2 = overload.2.max(load);
+                    }
+                    _ => overloads.push((span[0], span[1], load, items)),
+                }
+            }
 
-impl<'a> GanttChartTool<'a> {
-    pub fn new(log: &'a dyn GanttChartLog) -> GanttChartTool {
-        GanttChartTool { log }
+            for (start_date, end_date, load, items) in overloads {
+                warning!(
+                    self.log,
+                    "Resource '{}' is over-allocated at {}% from {} to {} by items {}",
+                    resource.name,
+                    load,
+                    start_date,
+                    end_date.pred(),
+                    items
+                        .iter()
+                        .map(|&i| format!("'{}'", outline[i].item.name()))
+                        .collect::<Vec<String>>()
+                        .join(", ")
+                );
+            }
+        }
     }
 
-    pub fn run(
-        self: &mut Self,
-        args: impl IntoIterator<Item = std::ffi::OsString>,
-    ) -> Result<(), Box<dyn Error>> {
-        let cli = match Cli::try_parse_from(args) {
-            Ok(cli) => cli,
-            Err(err) => {
-                output!(self.log, "{}", err.to_string());
-                return Ok(());
+    // The progress of every item, with summary items and the chart weighted by the duration of
+    // the items inside them. Items without a progress are complete unless they are open.
+    fn rollup_progress(outline: &[OutlineItem]) -> (Vec<u32>, u32) {
+        let percentage = |(done, days): (i64, i64)| {
+            if days == 0 {
+                0
+            } else {
+                ((done * 2 + days) / (days * 2)) as u32
             }
         };
+        let mut totals = vec![(0i64, 0i64); outline.len()];
+        let mut chart_total = (0, 0);
+        let mut progress = vec![0; outline.len()];
 
-        let chart_data = Self::read_chart_file(cli.get_input()?)?;
-        let render_data =
-            self.process_chart_data(cli.title_width, cli.max_month_width, &chart_data)?;
-        let document = self.render_chart(cli.add_resource_table, &render_data)?;
-
-        Self::write_svg_file(cli.get_output()?, &document)?;
-        Ok(())
-    }
-
-    fn read_chart_file(mut reader: Box<dyn Read>) -> Result<ChartData, Box<dyn Error>> {
-        let mut content = String::new();
+        // Children always come after their parent
+        for i in (0..outline.len()).rev() {
+            let item = outline[i].item;
 
-        reader.read_to_string(&mut content)?;
+            if outline[i].is_summary() {
+                progress[i] = percentage(totals[i]);
+            } else {
+                progress[i] =
+                    item.progress
+                        .unwrap_or(if item.open.unwrap_or(false) { 0 } else { 100 });
 
-        let chart_data: ChartData = json5::from_str(&content)?;
+                let days = item.duration.unwrap_or(0);
 
-        Ok(chart_data)
-    }
+                totals[i] = (days * progress[i] as i64, days);
+                chart_total.0 += totals[i].0;
+                chart_total.1 += totals[i].1;
+            }
 
-    fn write_svg_file(writer: Box<dyn Write>, document: &Document) -> Result<(), Box<dyn Error>> {
-        svg::write(writer, document)?;
+            if let Some(parent) = outline[i].parent {
+                totals[parent].0 += totals[i].0;
+                totals[parent].1 += totals[i].1;
+            }
+        }
 
-        Ok(())
+        (progress, percentage(chart_total))
     }
 
-    fn hsv_to_rgb(h: f32, s: f32, v: f32) -> u32 {
-        let h_i = (h * 6.0) as usize;
-        let f = h * 6.0 - h_i as f32;
-        let p = v * (1.0 - s);
-        let q = v * (1.0 - f * s);
-        let t = v * (1.0 - (1.0 - f) * s);
+    /// Schedule every item in the chart, warning about over-allocated resources
+    pub fn schedule(
+        &self,
+        chart_data: &ChartData,
+        level: bool,
+    ) -> Result<Schedule, Box<dyn Error>> {
+        let schedules = self.schedule_items(chart_data, level)?;
+        let project_end_date = |schedules: &[ItemSchedule]| {
+            schedules
+                .iter()
+                .map(|schedule| schedule.end_date())
+                .max()
+                .unwrap_or(NaiveDate::MIN)
+        };
 
-        fn rgb(r: f32, g: f32, b: f32) -> u32 {
-            ((r * 256.0) as u32) << 16 | ((g * 256.0) as u32) << 8 | ((b * 256.0) as u32)
+        if level {
+            let unleveled_end_date = project_end_date(&self.schedule_items(chart_data, false)?);
+            let leveled_end_date = project_end_date(&schedules);
+
+            if leveled_end_date != unleveled_end_date {
+                warning!(
+                    self.log,
+                    "Leveling moved the project end by {} day(s) from {} to {}",
+                    (leveled_end_date - unleveled_end_date).num_days(),
+                    unleveled_end_date,
+                    leveled_end_date
+                );
+            }
         }
 
-        if h_i == 0 {
-            rgb(v, t, p)
-        } else if h_i == 1 {
-            rgb(q, v, p)
-        } else if h_i == 2 {
-            rgb(p, v, t)
-        } else if h_i == 3 {
-            rgb(p, q, v)
-        } else if h_i == 4 {
-            rgb(t, p, v)
-        } else {
-            rgb(v, p, q)
-        }
+        self.check_allocations(chart_data, &schedules);
+
+        let outline = Self::outline_items(&chart_data.items);
+        let (progress, chart_progress) = Self::rollup_progress(&outline);
+        let chart_calendar = Self::chart_calendar(chart_data)?;
+        let calendars = Self::resource_calendars(chart_data)?;
+        let items = outline
+            .iter()
+            .zip(schedules.iter())
+            .enumerate()
+            .map(|(i, (entry, schedule))| {
+                let end_date = schedule.end_date();
+                let calendar = if entry.is_summary() {
+                    &chart_calendar
+                } else {
+                    &calendars[schedule.lead_resource_index()]
+                };
+
+                ScheduledItem {
+                    id: entry.item.id.clone(),
+                    title: entry.item.title.clone(),
+                    depth: entry.depth,
+                    parent: entry.parent,
+                    summary: entry.is_summary(),
+                    milestone: schedule.shadow_duration.is_none(),
+                    start_date: schedule.start_date,
+                    end_date,
+                    working_days: schedule
+                        .start_date
+                        .iter_days()
+                        .take_while(|date| *date < end_date)
+                        .filter(|date| calendar.is_working_day(*date))
+                        .count() as i64,
+                    assignments: schedule.assignments.clone(),
+                    late_start_date: schedule.late_start_date,
+                    total_float: schedule.total_float(),
+                    progress: progress[i],
+                }
+            })
+            .collect();
+
+        Ok(Schedule {
+            items,
+            start_date: schedules
+                .iter()
+                .map(|schedule| schedule.start_date)
+                .min()
+                .unwrap_or(NaiveDate::MIN),
+            end_date: project_end_date(&schedules),
+            progress: chart_progress,
+        })
     }
 
-    fn process_chart_data(
-        self: &Self,
-        title_width: f32,
-        max_month_width: f32,
+    /// Work out where everything in the chart goes
+    pub fn layout(
+        &self,
+        options: &ChartOptions,
         chart_data: &ChartData,
+        schedule: &Schedule,
     ) -> Result<RenderData, Box<dyn Error>> {
-        fn num_days_in_month(year: i32, month: u32) -> u32 {
-            // the first day of the next month...
-            let (y, m) = if month == 12 {
-                (year + 1, 1)
-            } else {
-                (year, month + 1)
-            };
-            let d = NaiveDate::from_ymd(y, m, 1);
+        let ChartOptions {
+            title_width,
+            max_month_width,
+            critical_path,
+            shade_weekends,
+            baseline: baseline_name,
+            time_scale,
+            from,
+            to,
+        } = *options;
 
-            // ...is preceded by the last day of the original month
-            d.pred().day()
-        }
+        let outline = Self::outline_items(&chart_data.items);
 
         // Fail if only one task
-        if chart_data.items.len() < 2 {
+        if outline.len() < 2 {
             bail!("You must provide more than one task");
         }
 
-        let mut start_date = NaiveDate::MAX;
-        let mut end_date = NaiveDate::MIN;
-        let mut date = NaiveDate::MIN;
-        let mut shadow_durations: Vec<Option<i64>> = Vec::with_capacity(chart_data.items.len());
+        // Rows are matched to scheduled items by position
+        if schedule.items.len() != outline.len()
+            || schedule
+                .items
+                .iter()
+                .zip(outline.iter())
+                .any(|(scheduled_item, entry)| scheduled_item.title != entry.item.title)
+        {
+            bail!("The schedule is not for the items in this chart");
+        }
 
-        // Determine the project start & end dates
-        for (i, item) in chart_data.items.iter().enumerate() {
-            if let Some(item_start_date) = item.start_date {
-                date = item_start_date;
-
-                if item_start_date < start_date {
-                    // Move the start if it falls on a weekend
-                    start_date = match date.weekday() {
-                        Weekday::Sat => date + Duration::days(2),
-                        Weekday::Sun => date + Duration::days(1),
-                        _ => date,
-                    };
-                }
-            } else if i == 0 {
-                return Err(From::from(format!("First item must contain a start date")));
+        let baseline = match baseline_name {
+            Some(name) => match chart_data.baselines.iter().find(|b| b.name == name) {
+                Some(baseline) => Some(baseline),
+                None => return Err(From::from(format!("There is no baseline named '{}'", name))),
+            },
+            None => chart_data.baselines.last(),
+        };
+        let mut baseline_dates: HashMap<&str, (NaiveDate, NaiveDate)> = HashMap::new();
+
+        for baseline_item in baseline.iter().flat_map(|baseline| baseline.items.iter()) {
+            if baseline_item.end_date < baseline_item.start_date {
+                return Err(From::from(format!(
+                    "Baseline item '{}' ends before it starts",
+                    baseline_item.id
+                )));
             }
 
-            // Skip the weekends and update a shadow list of the _real_ durations
-            if let Some(item_days) = item.duration {
-                let duration = match (date + Duration::days(item_days)).weekday() {
-                    Weekday::Sat => Duration::days(item_days + 2),
-                    Weekday::Sun => Duration::days(item_days + 1),
-                    _ => Duration::days(item_days),
-                };
+            baseline_dates.insert(
+                &baseline_item.id,
+                (baseline_item.start_date, baseline_item.end_date),
+            );
+        }
 
-                date += duration;
+        // Items are matched to the baseline by name, so each name must pick out a single item
+        if let Some(baseline) = baseline {
+            let duplicates = duplicate_names(baseline.items.iter().map(|item| item.id.as_str()));
 
-                shadow_durations.push(Some(duration.num_days()));
-            } else {
-                shadow_durations.push(None);
+            if !duplicates.is_empty() {
+                return Err(From::from(format!(
+                    "Baseline '{}' has more than one item with the id {}",
+                    baseline.name,
+                    duplicates.join(", ")
+                )));
             }
 
-            if end_date < date {
-                end_date = date;
-            }
+            let duplicates = duplicate_names(
+                outline
+                    .iter()
+                    .map(|entry| entry.item.name())
+                    .filter(|name| baseline_dates.contains_key(name)),
+            );
 
-            if let Some(item_resource_index) = item.resource_index {
-                if item_resource_index >= chart_data.resources.len() {
-                    return Err(From::from(format!("Resource index is out of range")));
-                }
-            } else if i == 0 {
+            if !duplicates.is_empty() {
                 return Err(From::from(format!(
-                    "First item must contain a resource index"
+                    "More than one item is named {}, so they cannot be compared with baseline \
+                     '{}'. Give them each a unique id",
+                    duplicates.join(", "),
+                    baseline.name
                 )));
             }
         }
 
-        start_date = NaiveDate::from_ymd(start_date.year(), start_date.month(), 1);
-        end_date = NaiveDate::from_ymd(
-            end_date.year(),
-            end_date.month(),
-            num_days_in_month(end_date.year(), end_date.month()),
-        );
+        // Determine the project start & end dates
+        let mut start_date = schedule.start_date;
+        let mut end_date = schedule.end_date;
+
+        for (baseline_start_date, baseline_end_date) in baseline_dates.values() {
+            start_date = start_date.min(*baseline_start_date);
+            end_date = end_date.max(*baseline_end_date);
+        }
+
+        // Only show part of the chart if asked to
+        start_date = from.unwrap_or(start_date);
+        end_date = to.unwrap_or(end_date);
+
+        if end_date < start_date {
+            bail!("The chart must end after it starts");
+        }
+
+        let calendar_year = FiscalYearData::default();
+        let fiscal_year = chart_data.fiscal_year.as_ref().unwrap_or(&calendar_year);
+
+        if !(1..=12).contains(&fiscal_year.start_month) {
+            bail!("The fiscal year start month must be from 1 to 12");
+        }
+
+        let time_scale = match time_scale {
+            TimeScale::Auto => TimeScale::for_days((end_date - start_date).num_days()),
+            time_scale => time_scale,
+        };
+
+        start_date = time_scale.start_of(start_date, fiscal_year);
+        end_date = time_scale.next(end_date, fiscal_year).pred();
 
         // Create all the column data
         let mut all_items_width: f32 = 0.0;
         let mut num_item_days: u32 = 0;
         let mut cols = vec![];
-
-        date = start_date;
+        let mut date = start_date;
 
         while date <= end_date {
-            let item_days = num_days_in_month(date.year(), date.month());
-            let item_width = max_month_width * (item_days as f32) / 31.0;
+            let next_date = time_scale.next(date, fiscal_year);
+            let item_days = (next_date - date).num_days() as u32;
+            let item_width = max_month_width * (item_days as f32) / time_scale.max_days();
 
             num_item_days += item_days;
             all_items_width += item_width;
 
             cols.push(ColumnRenderData {
                 width: item_width,
-                month_name: MONTH_NAMES[date.month() as usize - 1].to_string(),
+                label: time_scale.label(date, fiscal_year),
             });
 
-            date = NaiveDate::from_ymd(
-                date.year() + (if date.month() == 12 { 1 } else { 0 }),
-                date.month() % 12 + 1,
-                1,
-            );
+            date = next_date;
         }
 
-        date = start_date;
+        // Group the columns into longer periods for the upper row of the headings
+        let mut groups = vec![];
+
+        if let Some(group_scale) = time_scale.group() {
+            let mut date = start_date;
+
+            while date <= end_date {
+                let next_date = group_scale.next(date, fiscal_year).min(end_date.succ());
+
+                groups.push(ColumnRenderData {
+                    width: all_items_width * ((next_date - date).num_days() as f32)
+                        / (num_item_days as f32),
+                    label: match group_scale {
+                        TimeScale::Month => {
+                            format!("{} {}", group_scale.label(date, fiscal_year), date.year())
+                        }
+                        _ => group_scale.label(date, fiscal_year),
+                    },
+                });
+
+                date = next_date;
+            }
+        }
 
-        let mut resource_index: usize = 0;
         let gutter = Gutter {
             left: 10.0,
-            top: 80.0,
+            top: if groups.is_empty() { 80.0 } else { 110.0 },
             right: 10.0,
             bottom: 10.0,
         };
@@ -357,49 +2250,163 @@ impl<'a> GanttChartTool<'a> {
         let resource_height = resource_gutter.height() + 20.0;
         let mut rows = vec![];
 
-        // Calculate the X offsets of all the bars and milestones
-        for (i, item) in chart_data.items.iter().enumerate() {
-            if let Some(item_start_date) = item.start_date {
-                date = item_start_date;
+        // Shade each run of consecutive holidays, and optionally weekends, inside the chart
+        let day_width = all_items_width / (num_item_days as f32);
+        let shade = |dates: &mut dyn Iterator<Item = NaiveDate>| {
+            let mut shading: Vec<ShadingRenderData> = vec![];
+            let mut last_date = NaiveDate::MIN;
+
+            for date in dates {
+                match shading.last_mut() {
+                    Some(shade) if date == last_date.succ() => shade.width += day_width,
+                    _ => shading.push(ShadingRenderData {
+                        offset: title_width
+                            + gutter.left
+                            + ((date - start_date).num_days() as f32) * day_width,
+                        width: day_width,
+                    }),
+                }
+
+                last_date = date;
             }
 
+            shading
+        };
+        let chart_days = || start_date.iter_days().take_while(|date| *date <= end_date);
+        let calendar = Self::chart_calendar(chart_data)?;
+        let calendars = Self::resource_calendars(chart_data)?;
+        let holidays = shade(&mut chart_days().filter(|date| calendar.is_holiday(*date)));
+        let show_progress = outline.iter().any(|entry| entry.item.progress.is_some());
+
+        // Calculate the X offsets of all the bars and milestones
+        for (i, entry) in outline.iter().enumerate() {
+            let scheduled_item = &schedule.items[i];
             let offset = title_width
                 + gutter.left
-                + ((date - start_date).num_days() as f32) / (num_item_days as f32)
+                + ((scheduled_item.start_date - start_date).num_days() as f32)
+                    / (num_item_days as f32)
                     * all_items_width;
 
-            let mut length: Option<f32> = None;
+            // Use the elapsed days instead of the duration as they account for weekends
+            let length = (!scheduled_item.milestone).then(|| {
+                ((scheduled_item.end_date - scheduled_item.start_date).num_days() as f32)
+                    / (num_item_days as f32)
+                    * all_items_width
+            });
 
-            if let Some(item_days) = shadow_durations[i] {
-                // Use the shadow duration instead of the actual duration as it accounts for weekends
-                date += Duration::days(item_days);
-                length = Some((item_days as f32) / (num_item_days as f32) * all_items_width);
-            }
+            // Summary items have no resource of their own to shade for
+            let (weekends, vacations) = if entry.is_summary() {
+                (vec![], vec![])
+            } else {
+                let calendar = &calendars[scheduled_item.assignments[0].resource_index];
+                let weekends =
+                    if shade_weekends {
+                        shade(&mut chart_days().filter(|date| {
+                            calendar.is_weekend(*date) && !calendar.is_day_off(*date)
+                        }))
+                    } else {
+                        vec![]
+                    };
 
-            if let Some(item_resource_index) = item.resource_index {
-                resource_index = item_resource_index;
-            }
+                (
+                    weekends,
+                    shade(&mut chart_days().filter(|date| calendar.is_vacation(*date))),
+                )
+            };
+            let item = entry.item;
+            let date_offset = |date: NaiveDate| {
+                title_width
+                    + gutter.left
+                    + ((date - start_date).num_days() as f32) / (num_item_days as f32)
+                        * all_items_width
+            };
+            let baseline = baseline_dates.get(item.name()).map(|(start, end)| {
+                (
+                    BaselineRenderData {
+                        offset: date_offset(*start),
+                        length: (start < end).then(|| date_offset(*end) - date_offset(*start)),
+                    },
+                    (scheduled_item.end_date - *end).num_days(),
+                )
+            });
 
             rows.push(RowRenderData {
                 title: item.title.clone(),
-                resource_index,
+                depth: entry.depth,
+                parent: entry.parent,
+                summary: entry.is_summary(),
+                assignments: scheduled_item.assignments.clone(),
+                weekends,
+                vacations,
                 offset,
                 length,
                 open: item.open.unwrap_or(false),
+                progress: match item.progress {
+                    Some(_) => Some(scheduled_item.progress),
+                    None if show_progress && entry.is_summary() => Some(scheduled_item.progress),
+                    None => None,
+                },
+                critical: critical_path && scheduled_item.is_critical(),
+                slip: baseline
+                    .as_ref()
+                    .map(|(_, slip)| *slip)
+                    .filter(|slip| *slip != 0),
+                baseline: baseline.map(|(baseline, _)| baseline),
+                clipped_start: scheduled_item.start_date < start_date,
+                clipped_end: if scheduled_item.milestone {
+                    scheduled_item.start_date > end_date
+                } else {
+                    scheduled_item.end_date > end_date.succ()
+                },
             });
         }
 
-        let marked_date_offset = if let Some(date) = chart_data.marked_date {
-            // TODO(john): Put this offset calculation in a function
-            Some(
+        let mut resource_days = vec![0.0; chart_data.resources.len()];
+
+        for (entry, scheduled_item) in outline.iter().zip(schedule.items.iter()) {
+            for assignment in scheduled_item.assignments.iter() {
+                resource_days[assignment.resource_index] +=
+                    (entry.item.duration.unwrap_or(0) as f32) * (assignment.allocation as f32)
+                        / 100.0;
+            }
+        }
+
+        // Connect the rows of explicitly linked items
+        let ids: HashMap<&str, usize> = outline
+            .iter()
+            .enumerate()
+            .filter_map(|(i, entry)| entry.item.id.as_deref().map(|id| (id, i)))
+            .collect();
+        let mut links = vec![];
+
+        for (i, entry) in outline.iter().enumerate() {
+            for dependency in entry.item.depends_on.iter() {
+                if let Some(&j) = ids.get(dependency.id.as_str()) {
+                    links.push(LinkRenderData {
+                        from: j,
+                        to: i,
+                        kind: dependency.kind,
+                    });
+                }
+            }
+        }
+
+        let marked_date = match chart_data.marked_date {
+            Some(ref expr) => match expr.resolve(self.today)? {
+                Some(date) => Some(date),
+                None => bail!("The marked date cannot be relative to an item"),
+            },
+            None => None,
+        };
+        let marked_date_offset = marked_date
+            .filter(|date| (start_date..=end_date).contains(date))
+            .map(|date| {
+                // TODO(john): Put this offset calculation in a function
                 title_width
                     + gutter.left
                     + ((date - start_date).num_days() as f32) / (num_item_days as f32)
-                        * all_items_width,
-            )
-        } else {
-            None
-        };
+                        * all_items_width
+            });
 
         let mut styles = vec![
             ".outer-lines{stroke-width:3;stroke:#aaaaaa;}".to_owned(),
@@ -409,29 +2416,71 @@ impl<'a> GanttChartTool<'a> {
             ".title{font-family:Arial;font-size:18pt;}".to_owned(),
             ".heading{font-family:Arial;font-size:16pt;dominant-baseline:middle;text-anchor:middle;}".to_owned(),
             ".task-heading{dominant-baseline:middle;text-anchor:start;}".to_owned(),
+            ".summary-item{font-weight:bold;}".to_owned(),
+            ".summary{fill:#333333;stroke:none;}".to_owned(),
             ".milestone{fill:black;stroke-width:1;stroke:black;}".to_owned(),
             ".marker{stroke-width:2;stroke:#888888;stroke-dasharray:7;}".to_owned(),
+            ".holiday{fill:#eeeeee;stroke:none;}".to_owned(),
+            ".weekend{fill:#f6f6f6;stroke:none;}".to_owned(),
+            ".vacation{fill:#fbeee0;stroke:none;}".to_owned(),
+            ".dependency{fill:none;stroke-width:1.5;stroke:#555555;}".to_owned(),
+            ".dependency-arrow{fill:#555555;stroke:none;}".to_owned(),
         ];
 
         // Generate random resource colors based on https://martin.ankerl.com/2009/12/09/how-to-create-random-colors-programmatically/
         let mut rng = rand::thread_rng();
         let mut h: f32 = rng.gen();
+        let mut resource_colors = vec![];
 
-        for i in 0..chart_data.resources.len() {
-            let rgb = GanttChartTool::hsv_to_rgb(h, 0.5, 0.5);
+        for (i, resource) in chart_data.resources.iter().enumerate() {
+            let color = match resource.color {
+                Some(ref color) => color.clone(),
+                None => format!("#{:06x}", GanttChartTool::hsv_to_rgb(h, 0.5, 0.5)),
+            };
 
             styles.push(format!(
-                ".resource-{}-closed{{fill:#{1:06x};stroke-width:1;stroke:#{1:06x};}}",
-                i, rgb,
+                ".resource-{}-closed{{fill:{1};stroke-width:1;stroke:{1};}}",
+                i, color,
             ));
             styles.push(format!(
-                ".resource-{}-open{{fill:none;stroke-width:2;stroke:#{1:06x};}}",
-                i, rgb,
+                ".resource-{}-open{{fill:none;stroke-width:2;stroke:{1};}}",
+                i, color,
             ));
+            resource_colors.push(color);
 
             h = (h + GOLDEN_RATIO_CONJUGATE) % 1.0;
         }
 
+        if rows.iter().any(|row| row.clipped_start || row.clipped_end) {
+            styles.push(".clipped{fill:#555555;stroke:none;}".to_owned());
+        }
+
+        if !groups.is_empty() {
+            styles.push(".minor-lines{stroke-width:1;stroke:#eeeeee;}".to_owned());
+        }
+
+        if critical_path {
+            styles.push(".critical{stroke-width:3;stroke:#cc0000;}".to_owned());
+        }
+
+        if baseline.is_some() {
+            styles.push(".baseline{fill:#bbbbbb;stroke:none;}".to_owned());
+            styles.push(
+                ".slip{font-family:Arial;font-size:10pt;dominant-baseline:middle;fill:#cc0000;}"
+                    .to_owned(),
+            );
+            styles.push(".slip.early{fill:#008800;}".to_owned());
+        }
+
+        if show_progress {
+            styles.push(
+                ".progress{font-family:Arial;font-size:10pt;dominant-baseline:middle;}".to_owned(),
+            );
+            styles.push(
+                ".progress-total{font-family:Arial;font-size:14pt;text-anchor:end;}".to_owned(),
+            );
+        }
+
         Ok(RenderData {
             title: chart_data.title.to_owned(),
             gutter,
@@ -441,213 +2490,589 @@ impl<'a> GanttChartTool<'a> {
             resource_height,
             styles,
             title_width,
+            indent_width: 15.0,
             max_month_width,
             marked_date_offset,
             rect_corner_radius: 3.0,
+            time_scale,
             cols,
+            groups,
             rows,
-            resources: chart_data.resources.clone(),
+            links,
+            holidays,
+            resources: chart_data
+                .resources
+                .iter()
+                .map(|resource| resource.name.clone())
+                .collect(),
+            resource_colors,
+            resource_days,
+            progress: show_progress.then_some(schedule.progress),
         })
     }
+}
 
-    fn render_chart(
-        &self,
-        add_resource_table: bool,
-        rd: &RenderData,
-    ) -> Result<Document, Box<dyn Error>> {
-        let width: f32 = rd.gutter.left
-            + rd.title_width
-            + rd.cols.iter().map(|col| col.width).sum::<f32>()
-            + rd.gutter.right;
-        let height = rd.gutter.top
-            + (rd.rows.len() as f32 * rd.row_height)
-            + (if add_resource_table {
-                rd.resource_gutter.height() + rd.resource_height
-            } else {
-                0.0
-            })
-            + rd.gutter.bottom;
-
-        let mut document = Document::new()
-            .set("viewbox", (0, 0, width, height))
-            .set("xmlns", "http://www.w3.org/2000/svg")
-            .set("width", width)
-            .set("height", height)
-            .set("style", "background-color: white;");
-        let style = element::Style::new(rd.styles.join("\n"));
-
-        // Render all the chart rows
-        let mut rows = element::Group::new();
-
-        for i in 0..=rd.rows.len() {
-            let y = rd.gutter.top + (i as f32 * rd.row_height);
-
-            rows.append(if i == 0 || i == rd.rows.len() {
-                element::Line::new()
-                    .set("class", "outer-lines")
-                    .set("x1", rd.gutter.left)
-                    .set("y1", y)
-                    .set("x2", width - rd.gutter.right)
-                    .set("y2", y)
-            } else {
-                element::Line::new()
-                    .set("class", "inner-lines")
-                    .set("x1", rd.gutter.left)
-                    .set("y1", y)
-                    .set("x2", width - rd.gutter.right)
-                    .set("y2", y)
-            });
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
 
-            // Are we on one of the task rows?
-            if i < rd.rows.len() {
-                let row: &RowRenderData = &rd.rows[i];
+    struct TestLogger;
 
-                rows.append(
-                    element::Text::new(&row.title)
-                        .set("class", "item")
-                        .set("x", rd.gutter.left + rd.row_gutter.left)
-                        .set("y", y + rd.row_gutter.top + rd.row_height / 2.0),
-                );
+    impl GanttChartLog for TestLogger {
+        fn output(&self, _args: Arguments) {}
+        fn warning(&self, _args: Arguments) {}
+        fn error(&self, _args: Arguments) {}
+    }
 
-                // Is this a task or a milestone?
-                if let Some(length) = row.length {
-                    rows.append(
-                        element::Rectangle::new()
-                            .set(
-                                "class",
-                                format!(
-                                    "resource-{}{}",
-                                    row.resource_index,
-                                    if row.open { "-open" } else { "-closed" }
-                                ),
-                            )
-                            .set("x", row.offset)
-                            .set("y", y + rd.row_gutter.top)
-                            .set("rx", rd.rect_corner_radius)
-                            .set("ry", rd.rect_corner_radius)
-                            .set("width", length)
-                            .set("height", rd.row_height - rd.row_gutter.height()),
-                    );
-                } else {
-                    let n = (rd.row_height - rd.row_gutter.height()) / 2.0;
-                    rows.append(
-                        element::Path::new().set("class", "milestone").set(
-                            "d",
-                            Data::new()
-                                .move_to((row.offset - n, y + rd.row_gutter.top + n))
-                                .line_by((n, -n))
-                                .line_by((n, n))
-                                .line_by((-n, n))
-                                .line_by((-n, -n)),
-                        ),
-                    );
-                }
-            }
-        }
+    // Keeps the warnings so that tests can check them
+    #[derive(Default)]
+    struct WarningLogger {
+        warnings: RefCell<Vec<String>>,
+    }
 
-        // Render all the charts columns
-        let mut columns = element::Group::new();
-
-        for i in 0..=rd.cols.len() {
-            let x: f32 = rd.gutter.left
-                + rd.title_width
-                + rd.cols.iter().take(i).map(|col| col.width).sum::<f32>();
-            columns.append(
-                element::Line::new()
-                    .set("class", "inner-lines")
-                    .set("x1", x)
-                    .set("y1", rd.gutter.top)
-                    .set("x2", x)
-                    .set(
-                        "y2",
-                        rd.gutter.top + ((rd.rows.len() as f32) * rd.row_height),
-                    ),
-            );
+    impl GanttChartLog for WarningLogger {
+        fn output(&self, _args: Arguments) {}
+        fn warning(&self, args: Arguments) {
+            self.warnings.borrow_mut().push(args.to_string());
+        }
+        fn error(&self, _args: Arguments) {}
+    }
 
-            if i < rd.cols.len() {
-                columns.append(
-                    element::Text::new(&rd.cols[i].month_name)
-                        .set("class", "heading")
-                        .set("x", x + rd.max_month_width / 2.0)
-                        .set(
-                            "y",
-                            // TODO(john): Use a more appropriate row height value here?
-                            rd.gutter.top - rd.row_gutter.bottom - rd.row_height / 2.0,
-                        ),
-                );
-            }
+    fn options() -> ChartOptions<'static> {
+        ChartOptions {
+            title_width: 210.0,
+            max_month_width: 80.0,
+            critical_path: false,
+            shade_weekends: false,
+            baseline: None,
+            time_scale: TimeScale::Month,
+            from: None,
+            to: None,
         }
+    }
 
-        let tasks = element::Text::new("Tasks")
-            .set("class", "heading task-heading")
-            .set("x", rd.gutter.left + rd.row_gutter.left)
-            .set(
-                "y",
-                rd.gutter.top - rd.row_gutter.bottom - rd.row_height / 2.0,
-            );
+    fn schedule(text: &str) -> Result<Schedule, Box<dyn Error>> {
+        let chart_data: ChartData = json5::from_str(text).unwrap();
 
-        let title = element::Text::new(&rd.title)
-            .set("class", "title")
-            .set("x", rd.gutter.left)
-            // TODO(john): Use more appropriate row height value here?
-            .set("y", 25.0);
-
-        let marker: Box<dyn Node> = if let Some(offset) = rd.marked_date_offset {
-            Box::new(
-                element::Line::new()
-                    .set("class", "marker")
-                    .set("x1", offset)
-                    .set("y1", rd.gutter.top - 5.0)
-                    .set("x2", offset)
-                    .set(
-                        "y2",
-                        rd.gutter.top + ((rd.rows.len() as f32) * rd.row_height) + 5.0,
-                    ),
-            )
-        } else {
-            Box::new(element::Group::new())
+        GanttChartTool::new(&TestLogger).schedule(&chart_data, false)
+    }
+
+    // Schedule the chart with or without leveling, returning the warnings too
+    fn schedule_with_warnings(text: &str, level: bool) -> (Schedule, Vec<String>) {
+        let chart_data: ChartData = json5::from_str(text).unwrap();
+        let log = WarningLogger::default();
+        let schedule = GanttChartTool::new(&log)
+            .schedule(&chart_data, level)
+            .unwrap();
+
+        (schedule, log.warnings.into_inner())
+    }
+
+    fn render(text: &str, renderer: &dyn Renderer) -> String {
+        let chart_data: ChartData = json5::from_str(text).unwrap();
+        let tool = GanttChartTool::new(&TestLogger);
+        let schedule = tool.schedule(&chart_data, false).unwrap();
+        let render_data = tool.layout(&options(), &chart_data, &schedule).unwrap();
+        let mut output = vec![];
+
+        renderer
+            .render(&schedule, &render_data, &mut output)
+            .unwrap();
+
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn html_title_is_not_expanded() {
+        let html = render(
+            r#"{
+                title: "T {{svg}} <b>",
+                resources: ["</script>"],
+                items: [
+                    { title: "A", startDate: "2026-10-12", duration: 5, resource: 0 },
+                    { title: "B", duration: 3 },
+                ],
+            }"#,
+            &HtmlRenderer {
+                add_resource_table: false,
+            },
+        );
+
+        assert!(html.contains("<title>T {{svg}} &lt;b&gt;</title>"));
+        assert_eq!(html.matches("<svg").count(), 1);
+        assert_eq!(html.matches("</script>").count(), 1);
+    }
+
+    #[test]
+    fn layout_rejects_schedule_of_another_chart() {
+        let tool = GanttChartTool::new(&TestLogger);
+        let chart = |items: &str| -> ChartData {
+            json5::from_str(&format!(
+                "{{ title: \"T\", resources: [\"Dev\"], items: [{}] }}",
+                items
+            ))
+            .unwrap()
         };
+        let two_items = chart(
+            r#"{ title: "A", startDate: "2026-10-12", duration: 5, resource: 0 }, { title: "B", duration: 3 }"#,
+        );
+        let three_items = chart(
+            r#"{ title: "A", startDate: "2026-10-12", duration: 5, resource: 0 }, { title: "B", duration: 3 }, { title: "C", duration: 1 }"#,
+        );
+        let schedule = tool.schedule(&two_items, false).unwrap();
 
-        let mut resources = element::Group::new();
-
-        for i in 0..rd.resources.len() {
-            if add_resource_table {
-                let y = rd.gutter.top + ((rd.rows.len() as f32) * rd.row_height);
-                let block_width = rd.resource_height - rd.resource_gutter.height();
-
-                resources.append(
-                    element::Text::new(&rd.resources[i])
-                        .set("class", "resource")
-                        .set(
-                            "x",
-                            rd.resource_gutter.left + ((i + 1) as f32) * 100.0 - 5.0,
-                        )
-                        .set("y", y + rd.resource_height / 2.0),
-                );
-                resources.append(
-                    element::Rectangle::new()
-                        .set("class", format!("resource-{}-closed", i))
-                        .set(
-                            "x",
-                            rd.resource_gutter.left + ((i + 1) as f32) * 100.0 + 5.0,
-                        )
-                        .set("y", y + rd.resource_gutter.top)
-                        .set("rx", rd.rect_corner_radius)
-                        .set("ry", rd.rect_corner_radius)
-                        .set("width", block_width)
-                        .set("height", block_width),
-                );
-            }
+        assert!(tool.layout(&options(), &two_items, &schedule).is_ok());
+        assert!(tool.layout(&options(), &three_items, &schedule).is_err());
+    }
+
+    #[test]
+    fn workdays_predecessor_ending_on_friday_is_critical() {
+        let chart_data: ChartData = json5::from_str(
+            r#"{
+                title: "Workdays",
+                durationUnit: "workdays",
+                resources: ["Dev"],
+                items: [
+                    { id: "a", title: "A", startDate: "2026-10-12", duration: 5, resource: 0 },
+                    { id: "b", title: "B", duration: 3, dependsOn: ["a"] },
+                ],
+            }"#,
+        )
+        .unwrap();
+        let schedule = GanttChartTool::new(&TestLogger)
+            .schedule(&chart_data, false)
+            .unwrap();
+        let date = |s: &str| NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap();
+
+        assert_eq!(schedule.items[0].end_date, date("2026-10-19"));
+        assert_eq!(schedule.items[1].start_date, date("2026-10-19"));
+        assert_eq!(schedule.items[0].working_days, 5);
+        assert_eq!(schedule.items[0].total_float, 0);
+        assert_eq!(schedule.items[1].total_float, 0);
+    }
+
+    #[test]
+    fn lag_longer_than_a_century_is_an_error() {
+        for link in [
+            r#"dependsOn: [{ id: "a", lag: 9223372036854775807 }]"#,
+            r#"startDate: "end of previous item - 99999999d""#,
+        ] {
+            let error = schedule(&format!(
+                r#"{{
+                    title: "Lag",
+                    resources: ["Dev"],
+                    items: [
+                        {{ id: "a", title: "A", startDate: "2026-10-12", duration: 5, resource: 0 }},
+                        {{ id: "b", title: "B", duration: 3, {} }},
+                    ],
+                }}"#,
+                link
+            ))
+            .unwrap_err();
+
+            assert!(error.to_string().starts_with("Item 'b' has a lag of "));
+            assert!(error
+                .to_string()
+                .ends_with(" days, which is longer than the 36525 days allowed"));
         }
+    }
+
+    fn date(text: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(text, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn schedules_each_dependency_kind_with_lag() {
+        // 2026-10-12 is a Monday, and A takes up to the end of Wednesday
+        let schedule = schedule(
+            r#"{
+                title: "Links",
+                resources: ["Dev"],
+                items: [
+                    { id: "a", title: "A", startDate: "2026-10-12", duration: 3, resource: 0 },
+                    { id: "fs", title: "FS", duration: 1, dependsOn: [{ id: "a", type: "FS", lag: 1 }] },
+                    { id: "ss", title: "SS", duration: 2, dependsOn: [{ id: "a", type: "SS", lag: 1 }] },
+                    { id: "ff", title: "FF", duration: 2, dependsOn: [{ id: "a", type: "FF", lag: 1 }] },
+                    { id: "sf", title: "SF", duration: 2, dependsOn: [{ id: "a", type: "SF", lag: 4 }] },
+                    { id: "next", title: "Next", duration: 1 },
+                ],
+            }"#,
+        )
+        .unwrap();
+        let dates: Vec<(NaiveDate, NaiveDate)> = schedule
+            .items
+            .iter()
+            .map(|item| (item.start_date, item.end_date))
+            .collect();
+
+        assert_eq!(
+            dates,
+            vec![
+                (date("2026-10-12"), date("2026-10-15")),
+                (date("2026-10-16"), date("2026-10-19")),
+                (date("2026-10-13"), date("2026-10-15")),
+                (date("2026-10-14"), date("2026-10-16")),
+                (date("2026-10-14"), date("2026-10-16")),
+                (date("2026-10-16"), date("2026-10-19")),
+            ]
+        );
+    }
+
+    #[test]
+    fn schedules_items_after_predecessors_later_in_the_file() {
+        let schedule = schedule(
+            r#"{
+                title: "Order",
+                resources: ["Dev"],
+                items: [
+                    { id: "c", title: "C", duration: 1, resource: 0, dependsOn: ["b"] },
+                    { id: "b", title: "B", duration: 2, dependsOn: ["a"] },
+                    { id: "a", title: "A", startDate: "2026-10-12", duration: 1 },
+                ],
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(schedule.items[2].start_date, date("2026-10-12"));
+        assert_eq!(schedule.items[1].start_date, date("2026-10-13"));
+        assert_eq!(schedule.items[0].start_date, date("2026-10-15"));
+    }
+
+    #[test]
+    fn dependency_cycle_names_the_items() {
+        let error = schedule(
+            r#"{
+                title: "Cycle",
+                resources: ["Dev"],
+                items: [
+                    { id: "start", title: "Start", startDate: "2026-10-12", duration: 1, resource: 0 },
+                    { id: "a", title: "A", duration: 1, dependsOn: ["start", "c"] },
+                    { id: "b", title: "B", duration: 1, dependsOn: ["a"] },
+                    { id: "c", title: "C", duration: 1, dependsOn: ["b"] },
+                ],
+            }"#,
+        )
+        .unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "Dependency cycle detected between items 'b' -> 'c' -> 'a' -> 'b'"
+        );
+    }
+
+    #[test]
+    fn critical_path_follows_the_longest_branch() {
+        // 2026-10-05 is a Monday
+        let schedule = schedule(
+            r#"{
+                title: "Branches",
+                durationUnit: "workdays",
+                resources: ["Dev"],
+                items: [
+                    { id: "start", title: "Start", startDate: "2026-10-05", duration: 1, resource: 0 },
+                    { id: "long1", title: "Long 1", duration: 3, dependsOn: ["start"] },
+                    { id: "long2", title: "Long 2", duration: 3, dependsOn: ["long1"] },
+                    { id: "short", title: "Short", duration: 1, dependsOn: ["start"] },
+                    { id: "ss", title: "SS", duration: 1, dependsOn: [{ id: "long1", type: "SS", lag: 1 }] },
+                    { id: "ff", title: "FF", duration: 2, dependsOn: [{ id: "long2", type: "FF" }] },
+                    { id: "done", title: "Done", duration: 0, dependsOn: ["long2", "short", "ss", "ff"] },
+                ],
+            }"#,
+        )
+        .unwrap();
+        let floats: Vec<(&str, i64, bool)> = schedule
+            .items
+            .iter()
+            .map(|item| (item.title.as_str(), item.total_float, item.is_critical()))
+            .collect();
+
+        assert_eq!(
+            floats,
+            vec![
+                ("Start", 0, true),
+                ("Long 1", 0, true),
+                ("Long 2", 0, true),
+                ("Short", 7, false),
+                ("SS", 6, false),
+                ("FF", 0, true),
+                ("Done", 0, true),
+            ]
+        );
+    }
+
+    fn start_dates(schedule: &Schedule) -> Vec<NaiveDate> {
+        schedule.items.iter().map(|item| item.start_date).collect()
+    }
+
+    #[test]
+    fn leveling_books_higher_priority_items_first() {
+        // 2026-10-12 is a Monday
+        let chart = r#"{
+            title: "Priority",
+            resources: ["Dev"],
+            items: [
+                { title: "A", startDate: "2026-10-12", duration: 2, resource: 0 },
+                { title: "B", startDate: "2026-10-12", duration: 2, priority: 5 },
+            ],
+        }"#;
+        let (schedule, warnings) = schedule_with_warnings(chart, true);
+
+        assert_eq!(
+            start_dates(&schedule),
+            vec![date("2026-10-14"), date("2026-10-12")]
+        );
+        assert_eq!(
+            warnings,
+            vec!["Leveling moved the project end by 2 day(s) from 2026-10-14 to 2026-10-16"]
+        );
+
+        let (schedule, warnings) = schedule_with_warnings(chart, false);
+
+        assert_eq!(
+            start_dates(&schedule),
+            vec![date("2026-10-12"), date("2026-10-12")]
+        );
+        assert_eq!(
+            warnings,
+            vec![
+                "Resource 'Dev' is over-allocated at 200% from 2026-10-12 to 2026-10-13 by items \
+                 'A', 'B'"
+            ]
+        );
+    }
+
+    #[test]
+    fn leveling_pushes_items_past_existing_bookings() {
+        let (schedule, _) = schedule_with_warnings(
+            r#"{
+                title: "Booked",
+                resources: ["Dev", "Test"],
+                items: [
+                    { title: "A", startDate: "2026-10-12", duration: 3, resource: 0 },
+                    { title: "B", startDate: "2026-10-13", duration: 1 },
+                    { title: "C", startDate: "2026-10-13", duration: 1, resource: 1 },
+                ],
+            }"#,
+            true,
+        );
+
+        assert_eq!(
+            start_dates(&schedule),
+            vec![date("2026-10-12"), date("2026-10-15"), date("2026-10-13")]
+        );
+    }
+
+    #[test]
+    fn leveling_keeps_shared_allocations_together() {
+        let (schedule, warnings) = schedule_with_warnings(
+            r#"{
+                title: "Shared",
+                resources: ["Dev"],
+                items: [
+                    { title: "A", startDate: "2026-10-12", duration: 2, resource: [{ resource: 0, allocation: 50 }] },
+                    { title: "B", startDate: "2026-10-12", duration: 2, resource: [{ resource: 0, allocation: 50 }] },
+                ],
+            }"#,
+            true,
+        );
+
+        assert_eq!(
+            start_dates(&schedule),
+            vec![date("2026-10-12"), date("2026-10-12")]
+        );
+        assert!(warnings.is_empty());
+    }
+
+    fn holidays(content: &str) -> Vec<(Option<String>, NaiveDate, NaiveDate)> {
+        GanttChartTool::read_holidays_file(Box::new(io::Cursor::new(content.to_owned())))
+            .unwrap()
+            .into_iter()
+            .map(|holiday| (holiday.title, holiday.start_date, holiday.end_date))
+            .collect()
+    }
+
+    #[test]
+    fn reads_all_day_icalendar_events_with_exclusive_ends() {
+        let content = "BEGIN:VCALENDAR\r\n\
+                       VERSION:2.0\r\n\
+                       BEGIN:VEVENT\r\n\
+                       DTSTART;VALUE=DATE:20261225\r\n\
+                       DTEND;VALUE=DATE:20261226\r\n\
+                       SUMMARY:Christmas Day\r\n\
+                       END:VEVENT\r\n\
+                       BEGIN:VEVENT\r\n\
+                       DTSTART:20261228\r\n\
+                       DTEND:20270102\r\n\
+                       SUMMARY:Winter shutdown\r\n\
+                       END:VEVENT\r\n\
+                       BEGIN:VEVENT\r\n\
+                       DTSTART:20261126T090000\r\n\
+                       DTEND:20261126T170000\r\n\
+                       END:VEVENT\r\n\
+                       END:VCALENDAR\r\n";
+
+        assert_eq!(
+            holidays(content),
+            vec![
+                (
+                    Some("Christmas Day".to_owned()),
+                    date("2026-12-25"),
+                    date("2026-12-25")
+                ),
+                (
+                    Some("Winter shutdown".to_owned()),
+                    date("2026-12-28"),
+                    date("2027-01-01")
+                ),
+                (None, date("2026-11-26"), date("2026-11-26")),
+            ]
+        );
+    }
+
+    #[test]
+    fn unfolds_long_icalendar_lines() {
+        let content = "BEGIN:VCALENDAR\r\n\
+                       BEGIN:VEVENT\r\n\
+                       SUMMARY:Day after\r\n \
+                       Thanksgiving\r\n\
+                       DTSTART;VALUE=DATE:2026\r\n\t1127\r\n\
+                       END:VEVENT\r\n\
+                       END:VCALENDAR\r\n";
+
+        assert_eq!(
+            holidays(content),
+            vec![(
+                Some("Day afterThanksgiving".to_owned()),
+                date("2026-11-27"),
+                date("2026-11-27")
+            )]
+        );
+    }
+
+    #[test]
+    fn reads_dates_and_ranges_from_text() {
+        let content = "# Company holidays\n\
+                       2026-11-26 Thanksgiving\n\
+                       \n\
+                       2026-12-24..2027-01-01   Winter shutdown\n\
+                       2027-01-18\n";
+
+        assert_eq!(
+            holidays(content),
+            vec![
+                (
+                    Some("Thanksgiving".to_owned()),
+                    date("2026-11-26"),
+                    date("2026-11-26")
+                ),
+                (
+                    Some("Winter shutdown".to_owned()),
+                    date("2026-12-24"),
+                    date("2027-01-01")
+                ),
+                (None, date("2027-01-18"), date("2027-01-18")),
+            ]
+        );
+        assert!(GanttChartTool::read_holidays_file(Box::new(io::Cursor::new(
+            "2026-11-26\n2026-13-01\n"
+        )))
+        .unwrap_err()
+        .to_string()
+        .starts_with("Invalid holiday date '2026-13-01' on line 2"));
+    }
 
-        document.append(style);
-        document.append(title);
-        document.append(columns);
-        document.append(tasks);
-        document.append(rows);
-        document.append(marker);
-        document.append(resources);
+    fn layout_error(items: &str, baseline_items: &str) -> String {
+        let chart_data: ChartData = json5::from_str(&format!(
+            r#"{{
+                title: "Baseline",
+                resources: ["Dev"],
+                items: [{}],
+                baselines: [{{ name: "March", items: [{}] }}],
+            }}"#,
+            items, baseline_items
+        ))
+        .unwrap();
+        let tool = GanttChartTool::new(&TestLogger);
+        let schedule = tool.schedule(&chart_data, false).unwrap();
+
+        tool.layout(&options(), &chart_data, &schedule)
+            .unwrap_err()
+            .to_string()
+    }
+
+    #[test]
+    fn baseline_names_must_pick_out_one_item() {
+        let items = r#"
+            { title: "Spec", startDate: "2026-10-12", duration: 2, resource: 0 },
+            { title: "Spec", duration: 3 },
+            { id: "build", title: "Build", duration: 1 },
+        "#;
+
+        assert_eq!(
+            layout_error(
+                items,
+                r#"{ id: "Spec", startDate: "2026-10-12", endDate: "2026-10-14" }"#
+            ),
+            "More than one item is named 'Spec', so they cannot be compared with baseline \
+             'March'. Give them each a unique id"
+        );
+        assert_eq!(
+            layout_error(
+                items,
+                r#"
+                    { id: "build", startDate: "2026-10-12", endDate: "2026-10-14" },
+                    { id: "build", startDate: "2026-10-14", endDate: "2026-10-15" },
+                "#
+            ),
+            "Baseline 'March' has more than one item with the id 'build'"
+        );
+    }
+
+    #[test]
+    fn baseline_saves_exclusive_end_dates_and_unique_names() {
+        let saved = schedule(
+            r#"{
+                title: "Save",
+                resources: ["Dev"],
+                items: [
+                    { id: "spec", title: "Spec", startDate: "2026-10-12", duration: 3, resource: 0 },
+                    { title: "Done", duration: 0 },
+                ],
+            }"#,
+        )
+        .unwrap();
+        let baseline = saved.baseline("March").unwrap();
+        let dates: Vec<(&str, NaiveDate, NaiveDate)> = baseline
+            .items
+            .iter()
+            .map(|item| (item.id.as_str(), item.start_date, item.end_date))
+            .collect();
+
+        assert_eq!(
+            dates,
+            vec![
+                ("spec", date("2026-10-12"), date("2026-10-15")),
+                ("Done", date("2026-10-15"), date("2026-10-15")),
+            ]
+        );
 
-        Ok(document)
+        let shared = schedule(
+            r#"{
+                title: "Save",
+                resources: ["Dev"],
+                items: [
+                    { title: "Spec", startDate: "2026-10-12", duration: 3, resource: 0 },
+                    { title: "Spec", duration: 2 },
+                ],
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            shared.baseline("March").unwrap_err().to_string(),
+            "More than one item is named 'Spec', so baseline 'March' cannot be saved. Give them \
+             each a unique id"
+        );
     }
 }
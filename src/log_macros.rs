@@ -25,4 +25,4 @@ macro_rules! error {
   ($log: expr, $fmt: expr, $($args: tt)+) => {
     $log.error(format_args!($fmt, $($args)+))
   };
-}
\ No newline at end of file
+}
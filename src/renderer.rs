@@ -0,0 +1,1594 @@
+use crate::{DependencyKind, RenderData, RowRenderData, Schedule};
+use clap::ValueEnum;
+#[cfg(feature = "png")]
+use easy_error::bail;
+use serde::Serialize;
+use std::{error::Error, io::Write};
+use svg::{
+    node::{element::path::Data, Node, *},
+    Document,
+};
+
+static HTML_TEMPLATE: &str = include_str!("chart.html");
+
+// Fonts like the Arial used by the chart styles, in order of preference
+#[cfg(any(feature = "png", feature = "pdf"))]
+static SANS_SERIF_FAMILIES: [&str; 4] = ["Arial", "Helvetica", "Liberation Sans", "DejaVu Sans"];
+
+// Parse the chart with the system fonts for rasterizing or converting it
+#[cfg(any(feature = "png", feature = "pdf"))]
+fn svg_tree(document: &Document) -> Result<usvg::Tree, Box<dyn Error>> {
+    let mut options = usvg::Options::default();
+
+    // Fonts that are missing fall back to the serif font, so make that a similar sans-serif
+    let fontdb = options.fontdb_mut();
+
+    fontdb.load_system_fonts();
+
+    if let Some(family) = SANS_SERIF_FAMILIES.into_iter().find(|family| {
+        fontdb
+            .faces()
+            .any(|face| face.families.iter().any(|(name, _)| name == family))
+    }) {
+        fontdb.set_serif_family(family);
+    }
+
+    Ok(usvg::Tree::from_str(&document.to_string(), &options)?)
+}
+
+/// Writes a scheduled and laid out chart in some output format
+pub trait Renderer {
+    fn render(
+        &self,
+        schedule: &Schedule,
+        render_data: &RenderData,
+        writer: &mut dyn Write,
+    ) -> Result<(), Box<dyn Error>>;
+}
+
+/// Renders the chart as an SVG image
+#[derive(Debug, Default)]
+pub struct SvgRenderer {
+    /// Add a resource table at the bottom of the chart
+    pub add_resource_table: bool,
+    /// Fill the image with a CSS color before drawing the chart
+    pub background: Option<String>,
+}
+
+impl SvgRenderer {
+    /// Build the SVG document for the chart
+    pub fn document(&self, rd: &RenderData) -> Result<Document, Box<dyn Error>> {
+        let add_resource_table = self.add_resource_table;
+        let width: f32 = rd.gutter.left
+            + rd.title_width
+            + rd.cols.iter().map(|col| col.width).sum::<f32>()
+            + rd.gutter.right;
+        let height = rd.gutter.top
+            + (rd.rows.len() as f32 * rd.row_height)
+            + (if add_resource_table {
+                rd.resource_gutter.height() + rd.resource_height
+            } else {
+                0.0
+            })
+            + rd.gutter.bottom;
+
+        let mut document = Document::new()
+            .set("viewbox", (0, 0, width, height))
+            .set("xmlns", "http://www.w3.org/2000/svg")
+            .set("width", width)
+            .set("height", height)
+            .set("style", "background-color: white;");
+        let style = element::Style::new(rd.styles.join("\n"));
+
+        // Render all the chart rows, with the bars cut off at the edges of the chart
+        let mut rows = element::Group::new();
+        let mut bars = element::Group::new();
+        let chart_left = rd.gutter.left + rd.title_width;
+        let chart_right = width - rd.gutter.right;
+        let clipped = rd
+            .rows
+            .iter()
+            .any(|row| row.clipped_start || row.clipped_end);
+
+        for i in 0..=rd.rows.len() {
+            let y = rd.gutter.top + (i as f32 * rd.row_height);
+
+            rows.append(if i == 0 || i == rd.rows.len() {
+                element::Line::new()
+                    .set("class", "outer-lines")
+                    .set("data-row", i)
+                    .set("x1", rd.gutter.left)
+                    .set("y1", y)
+                    .set("x2", width - rd.gutter.right)
+                    .set("y2", y)
+            } else {
+                element::Line::new()
+                    .set("class", "inner-lines")
+                    .set("data-row", i)
+                    .set("x1", rd.gutter.left)
+                    .set("y1", y)
+                    .set("x2", width - rd.gutter.right)
+                    .set("y2", y)
+            });
+
+            // Are we on one of the task rows?
+            if i < rd.rows.len() {
+                let row: &RowRenderData = &rd.rows[i];
+
+                rows.append(
+                    element::Text::new(&row.title)
+                        .set(
+                            "class",
+                            if row.summary {
+                                "item summary-item"
+                            } else {
+                                "item"
+                            },
+                        )
+                        .set("data-row", i)
+                        .set(
+                            "x",
+                            rd.gutter.left
+                                + rd.row_gutter.left
+                                + (row.depth as f32) * rd.indent_width,
+                        )
+                        .set("y", y + rd.row_gutter.top + rd.row_height / 2.0),
+                );
+
+                // Is this a summary, a task or a milestone?
+                if let (true, Some(length)) = (row.summary, row.length) {
+                    // A bracket spanning the children, with a point hanging down at each end
+                    let bar_height = (rd.row_height - rd.row_gutter.height()) / 3.0;
+                    let point = bar_height.min(length / 2.0);
+
+                    bars.append(
+                        element::Path::new()
+                            .set(
+                                "class",
+                                if row.critical {
+                                    "summary critical"
+                                } else {
+                                    "summary"
+                                },
+                            )
+                            .set("data-row", i)
+                            .set(
+                                "d",
+                                Data::new()
+                                    .move_to((row.offset, y + rd.row_gutter.top))
+                                    .horizontal_line_by(length)
+                                    .vertical_line_by(bar_height * 2.0)
+                                    .line_by((-point, -bar_height))
+                                    .horizontal_line_to(row.offset + point)
+                                    .line_by((-point, bar_height))
+                                    .close(),
+                            ),
+                    );
+                } else if let Some(length) = row.length {
+                    // Shared items are split into a stripe for each resource, sized by allocation
+                    let bar_height = rd.row_height - rd.row_gutter.height();
+                    let total_allocation: u32 = row.assignments.iter().map(|a| a.allocation).sum();
+                    let corner_radius = if row.assignments.len() == 1 {
+                        rd.rect_corner_radius
+                    } else {
+                        0.0
+                    };
+                    let mut stripe_y = y + rd.row_gutter.top;
+
+                    for assignment in row.assignments.iter() {
+                        let stripe_height =
+                            bar_height * (assignment.allocation as f32) / (total_allocation as f32);
+
+                        bars.append(
+                            element::Rectangle::new()
+                                .set(
+                                    "class",
+                                    format!(
+                                        "resource-{}{}{}",
+                                        assignment.resource_index,
+                                        if row.open || row.progress.is_some() {
+                                            "-open"
+                                        } else {
+                                            "-closed"
+                                        },
+                                        if row.critical { " critical" } else { "" }
+                                    ),
+                                )
+                                .set("data-row", i)
+                                .set("x", row.offset)
+                                .set("y", stripe_y)
+                                .set("rx", corner_radius)
+                                .set("ry", corner_radius)
+                                .set("width", length)
+                                .set("height", stripe_height),
+                        );
+
+                        // Fill in the part of the bar that is complete
+                        if let Some(progress) = row.progress.filter(|progress| *progress > 0) {
+                            bars.append(
+                                element::Rectangle::new()
+                                    .set(
+                                        "class",
+                                        format!("resource-{}-closed", assignment.resource_index),
+                                    )
+                                    .set("data-row", i)
+                                    .set("x", row.offset)
+                                    .set("y", stripe_y)
+                                    .set("rx", corner_radius)
+                                    .set("ry", corner_radius)
+                                    .set("width", length * (progress as f32) / 100.0)
+                                    .set("height", stripe_height),
+                            );
+                        }
+
+                        stripe_y += stripe_height;
+                    }
+                } else {
+                    let n = (rd.row_height - rd.row_gutter.height()) / 2.0;
+                    bars.append(
+                        element::Path::new()
+                            .set(
+                                "class",
+                                if row.critical {
+                                    "milestone critical"
+                                } else {
+                                    "milestone"
+                                },
+                            )
+                            .set("data-row", i)
+                            .set(
+                                "d",
+                                Data::new()
+                                    .move_to((row.offset - n, y + rd.row_gutter.top + n))
+                                    .line_by((n, -n))
+                                    .line_by((n, n))
+                                    .line_by((-n, n))
+                                    .line_by((-n, -n)),
+                            ),
+                    );
+                }
+
+                // Show the baseline as a thin bar in the gutter under the item
+                if let Some(ref baseline) = row.baseline {
+                    let baseline_y = y + rd.row_height - rd.row_gutter.bottom;
+                    let baseline_height = rd.row_gutter.bottom - 1.0;
+
+                    bars.append(match baseline.length {
+                        Some(length) => element::Path::new()
+                            .set("class", "baseline")
+                            .set("data-row", i)
+                            .set(
+                                "d",
+                                Data::new()
+                                    .move_to((baseline.offset, baseline_y))
+                                    .horizontal_line_by(length)
+                                    .vertical_line_by(baseline_height)
+                                    .horizontal_line_by(-length)
+                                    .close(),
+                            ),
+                        None => element::Path::new()
+                            .set("class", "baseline")
+                            .set("data-row", i)
+                            .set(
+                                "d",
+                                Data::new()
+                                    .move_to((baseline.offset, baseline_y))
+                                    .line_by((baseline_height / 2.0, baseline_height / 2.0))
+                                    .line_by((-baseline_height / 2.0, baseline_height / 2.0))
+                                    .line_by((-baseline_height / 2.0, -baseline_height / 2.0))
+                                    .close(),
+                            ),
+                    });
+                }
+
+                // Label the end of the item with its progress and how far it has slipped
+                let mut label_x = row.offset
+                    + row
+                        .length
+                        .unwrap_or((rd.row_height - rd.row_gutter.height()) / 2.0)
+                    + rd.row_gutter.left;
+                let label_y = y + rd.row_gutter.top + rd.row_height / 2.0;
+
+                // Keep the labels of items that end before the chart starts in sight
+                if row.clipped_start {
+                    label_x = label_x.max(
+                        chart_left
+                            + (rd.row_height - rd.row_gutter.height()) / 2.0
+                            + rd.row_gutter.left,
+                    );
+                }
+
+                if let (Some(progress), Some(_)) = (row.progress, row.length) {
+                    bars.append(
+                        element::Text::new(format!("{}%", progress))
+                            .set("class", "progress")
+                            .set("data-row", i)
+                            .set("x", label_x)
+                            .set("y", label_y),
+                    );
+
+                    label_x += 40.0;
+                }
+
+                if let Some(slip) = row.slip {
+                    bars.append(
+                        element::Text::new(format!("{:+}d", slip))
+                            .set("class", if slip > 0 { "slip" } else { "slip early" })
+                            .set("data-row", i)
+                            .set("x", label_x)
+                            .set("y", label_y),
+                    );
+                }
+
+                // Point off the edge of the chart at the part of the item that is cut off
+                let n = (rd.row_height - rd.row_gutter.height()) / 2.0;
+                let arrow_y = y + rd.row_gutter.top + n;
+
+                if row.clipped_start {
+                    bars.append(
+                        element::Path::new()
+                            .set("class", "clipped")
+                            .set("data-row", i)
+                            .set(
+                                "d",
+                                Data::new()
+                                    .move_to((chart_left, arrow_y))
+                                    .line_by((n, -n))
+                                    .vertical_line_by(n * 2.0)
+                                    .close(),
+                            ),
+                    );
+                }
+
+                if row.clipped_end {
+                    bars.append(
+                        element::Path::new()
+                            .set("class", "clipped")
+                            .set("data-row", i)
+                            .set(
+                                "d",
+                                Data::new()
+                                    .move_to((chart_right, arrow_y))
+                                    .line_by((-n, -n))
+                                    .vertical_line_by(n * 2.0)
+                                    .close(),
+                            ),
+                    );
+                }
+            }
+        }
+
+        // Render all the charts columns
+        let mut columns = element::Group::new();
+
+        for holiday in rd.holidays.iter() {
+            columns.append(
+                element::Rectangle::new()
+                    .set("class", "holiday")
+                    .set("x", holiday.offset)
+                    .set("y", rd.gutter.top)
+                    .set("width", holiday.width)
+                    .set("height", (rd.rows.len() as f32) * rd.row_height),
+            );
+        }
+
+        for (i, row) in rd.rows.iter().enumerate() {
+            let shading = row
+                .weekends
+                .iter()
+                .map(|shade| ("weekend", shade))
+                .chain(row.vacations.iter().map(|shade| ("vacation", shade)));
+
+            for (class, shade) in shading {
+                columns.append(
+                    element::Rectangle::new()
+                        .set("class", class)
+                        .set("data-row", i)
+                        .set("x", shade.offset)
+                        .set("y", rd.gutter.top + (i as f32 * rd.row_height))
+                        .set("width", shade.width)
+                        .set("height", rd.row_height),
+                );
+            }
+        }
+
+        for i in 0..=rd.cols.len() {
+            let x: f32 = rd.gutter.left
+                + rd.title_width
+                + rd.cols.iter().take(i).map(|col| col.width).sum::<f32>();
+            columns.append(
+                element::Line::new()
+                    .set(
+                        "class",
+                        if rd.groups.is_empty() {
+                            "inner-lines"
+                        } else {
+                            "minor-lines"
+                        },
+                    )
+                    .set("x1", x)
+                    .set("y1", rd.gutter.top)
+                    .set("x2", x)
+                    .set(
+                        "y2",
+                        rd.gutter.top + ((rd.rows.len() as f32) * rd.row_height),
+                    ),
+            );
+
+            if i < rd.cols.len() {
+                columns.append(
+                    element::Text::new(&rd.cols[i].label)
+                        .set("class", "heading")
+                        .set("x", x + rd.max_month_width / 2.0)
+                        .set(
+                            "y",
+                            // TODO(john): Use a more appropriate row height value here?
+                            rd.gutter.top - rd.row_gutter.bottom - rd.row_height / 2.0,
+                        ),
+                );
+            }
+        }
+
+        // Separate the groups of columns, and label the groups that have room
+        if !rd.groups.is_empty() {
+            for i in 0..=rd.groups.len() {
+                let x: f32 = rd.gutter.left
+                    + rd.title_width
+                    + rd.groups
+                        .iter()
+                        .take(i)
+                        .map(|group| group.width)
+                        .sum::<f32>();
+                columns.append(
+                    element::Line::new()
+                        .set("class", "inner-lines")
+                        .set("x1", x)
+                        .set("y1", rd.gutter.top - rd.row_height)
+                        .set("x2", x)
+                        .set(
+                            "y2",
+                            rd.gutter.top + ((rd.rows.len() as f32) * rd.row_height),
+                        ),
+                );
+
+                match rd.groups.get(i) {
+                    Some(group) if group.width >= group.label.len() as f32 * 12.0 => {
+                        columns.append(
+                            element::Text::new(&group.label)
+                                .set("class", "heading")
+                                .set("x", x + group.width / 2.0)
+                                .set(
+                                    "y",
+                                    rd.gutter.top - rd.row_gutter.bottom - rd.row_height * 1.5,
+                                ),
+                        );
+                    }
+                    _ => (),
+                }
+            }
+        }
+
+        let tasks = element::Text::new("Tasks")
+            .set("class", "heading task-heading")
+            .set("x", rd.gutter.left + rd.row_gutter.left)
+            .set(
+                "y",
+                rd.gutter.top - rd.row_gutter.bottom - rd.row_height / 2.0,
+            );
+
+        let title = element::Text::new(&rd.title)
+            .set("class", "title")
+            .set("x", rd.gutter.left)
+            // TODO(john): Use more appropriate row height value here?
+            .set("y", 25.0);
+
+        let progress: Box<dyn Node> = if let Some(progress) = rd.progress {
+            Box::new(
+                element::Text::new(format!("{}% complete", progress))
+                    .set("class", "progress-total")
+                    .set("x", width - rd.gutter.right)
+                    .set("y", 25.0),
+            )
+        } else {
+            Box::new(element::Group::new())
+        };
+
+        // Render the dependency connectors, routing around the bars when the successor starts too soon
+        let mut links = element::Group::new();
+        let n = (rd.row_height - rd.row_gutter.height()) / 2.0;
+        let gap = rd.row_gutter.width();
+        let arrow_size = 6.0;
+        let row_extent = |row: &RowRenderData| match row.length {
+            Some(length) => (row.offset, row.offset + length),
+            None => (row.offset - n, row.offset + n),
+        };
+
+        for link in rd.links.iter() {
+            let (from_start, from_end) = row_extent(&rd.rows[link.from]);
+            let (to_start, to_end) = row_extent(&rd.rows[link.to]);
+            // Each end of the connector has an x position and the direction the line travels there
+            let (x1, d1) = match link.kind {
+                DependencyKind::FinishToStart | DependencyKind::FinishToFinish => (from_end, 1.0),
+                DependencyKind::StartToStart | DependencyKind::StartToFinish => (from_start, -1.0),
+            };
+            let (x2, d2) = match link.kind {
+                DependencyKind::FinishToStart | DependencyKind::StartToStart => (to_start, 1.0),
+                DependencyKind::FinishToFinish | DependencyKind::StartToFinish => (to_end, -1.0),
+            };
+            let y1 = rd.gutter.top + (link.from as f32 * rd.row_height) + rd.row_height / 2.0;
+            let y2 = rd.gutter.top + (link.to as f32 * rd.row_height) + rd.row_height / 2.0;
+            let exit_x = x1 + d1 * gap;
+            let entry_x = x2 - d2 * gap;
+            let mut data = Data::new().move_to((x1, y1));
+
+            data = if (x2 - exit_x) * d2 >= gap {
+                data.horizontal_line_to(exit_x).vertical_line_to(y2)
+            } else if (entry_x - x1) * d1 >= gap {
+                data.horizontal_line_to(entry_x).vertical_line_to(y2)
+            } else {
+                data.horizontal_line_to(exit_x)
+                    .vertical_line_to(y2 - (y2 - y1).signum() * rd.row_height / 2.0)
+                    .horizontal_line_to(entry_x)
+                    .vertical_line_to(y2)
+            };
+
+            links.append(
+                element::Path::new()
+                    .set("class", "dependency")
+                    .set("data-from", link.from)
+                    .set("data-to", link.to)
+                    .set("d", data.horizontal_line_to(x2 - d2 * arrow_size)),
+            );
+            links.append(
+                element::Path::new()
+                    .set("class", "dependency-arrow")
+                    .set("data-from", link.from)
+                    .set("data-to", link.to)
+                    .set(
+                        "d",
+                        Data::new()
+                            .move_to((x2, y2))
+                            .line_by((-d2 * arrow_size, -arrow_size / 2.0))
+                            .line_by((0.0, arrow_size))
+                            .close(),
+                    ),
+            );
+        }
+
+        let marker: Box<dyn Node> = if let Some(offset) = rd.marked_date_offset {
+            Box::new(
+                element::Line::new()
+                    .set("class", "marker")
+                    .set("x1", offset)
+                    .set("y1", rd.gutter.top - 5.0)
+                    .set("x2", offset)
+                    .set(
+                        "y2",
+                        rd.gutter.top + ((rd.rows.len() as f32) * rd.row_height) + 5.0,
+                    ),
+            )
+        } else {
+            Box::new(element::Group::new())
+        };
+
+        let mut resources = element::Group::new();
+        // Where the current label ends and its color block starts
+        let mut x = rd.resource_gutter.left;
+
+        for i in 0..rd.resources.len() {
+            if add_resource_table {
+                let y = rd.gutter.top + ((rd.rows.len() as f32) * rd.row_height);
+                let block_width = rd.resource_height - rd.resource_gutter.height();
+
+                let days = rd.resource_days[i];
+                let label = if days.fract() == 0.0 {
+                    format!("{} ({}d)", rd.resources[i], days)
+                } else {
+                    format!("{} ({:.1}d)", rd.resources[i], days)
+                };
+
+                // Leave room for long labels, guessing at 8 pixels for each character
+                x += (label.chars().count() as f32 * 8.0 + block_width + 25.0).max(100.0);
+                resources.append(
+                    element::Text::new(label)
+                        .set("class", "resource")
+                        .set("data-row", rd.rows.len())
+                        .set("x", x - 5.0)
+                        .set("y", y + rd.resource_height / 2.0),
+                );
+                resources.append(
+                    element::Rectangle::new()
+                        .set("class", format!("resource-{}-closed", i))
+                        .set("data-row", rd.rows.len())
+                        .set("x", x + 5.0)
+                        .set("y", y + rd.resource_gutter.top)
+                        .set("rx", rd.rect_corner_radius)
+                        .set("ry", rd.rect_corner_radius)
+                        .set("width", block_width)
+                        .set("height", block_width),
+                );
+            }
+        }
+
+        if let Some(ref background) = self.background {
+            document.append(
+                element::Rectangle::new()
+                    .set("fill", background.as_str())
+                    .set("width", "100%")
+                    .set("height", "100%"),
+            );
+        }
+
+        document.append(style);
+        document.append(title);
+        document.append(progress);
+        document.append(columns);
+        document.append(tasks);
+        if clipped {
+            document.append(
+                element::Definitions::new().add(
+                    element::ClipPath::new().set("id", "chart-window").add(
+                        element::Rectangle::new()
+                            .set("x", chart_left)
+                            .set("y", 0)
+                            .set("width", chart_right - chart_left)
+                            .set("height", height),
+                    ),
+                ),
+            );
+            bars = bars.set("clip-path", "url(#chart-window)");
+            links = links.set("clip-path", "url(#chart-window)");
+        }
+
+        document.append(rows);
+        document.append(bars);
+        document.append(links);
+        document.append(marker);
+        document.append(resources);
+
+        Ok(document)
+    }
+}
+
+impl Renderer for SvgRenderer {
+    fn render(
+        &self,
+        _schedule: &Schedule,
+        render_data: &RenderData,
+        writer: &mut dyn Write,
+    ) -> Result<(), Box<dyn Error>> {
+        svg::write(writer, &self.document(render_data)?)?;
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct HtmlRowData {
+    parent: Option<usize>,
+    summary: bool,
+    resources: Vec<usize>,
+}
+
+/// Renders the chart as an HTML page with collapsible groups, a resource filter and zooming
+#[derive(Debug, Default)]
+pub struct HtmlRenderer {
+    /// Add a resource table at the bottom of the chart
+    pub add_resource_table: bool,
+}
+
+impl Renderer for HtmlRenderer {
+    fn render(
+        &self,
+        _schedule: &Schedule,
+        rd: &RenderData,
+        writer: &mut dyn Write,
+    ) -> Result<(), Box<dyn Error>> {
+        let document = SvgRenderer {
+            add_resource_table: self.add_resource_table,
+            background: None,
+        }
+        .document(rd)?;
+
+        // What the page script needs to know to hide and show rows
+        let rows: Vec<HtmlRowData> = rd
+            .rows
+            .iter()
+            .map(|row| HtmlRowData {
+                parent: row.parent,
+                summary: row.summary,
+                resources: row
+                    .assignments
+                    .iter()
+                    .map(|assignment| assignment.resource_index)
+                    .collect(),
+            })
+            .collect();
+        let title = rd
+            .title
+            .replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;");
+        // Keep names like "</script>" from ending the script early
+        let script_json = |json: String| json.replace("</", "<\\/");
+        let html = fill_template(
+            HTML_TEMPLATE,
+            &[
+                ("title", title),
+                ("rows", script_json(json5::to_string(&rows)?)),
+                ("resources", script_json(json5::to_string(&rd.resources)?)),
+                ("top", rd.gutter.top.to_string()),
+                ("row_height", rd.row_height.to_string()),
+                ("svg", document.to_string()),
+            ],
+        );
+
+        writer.write_all(html.as_bytes())?;
+
+        Ok(())
+    }
+}
+
+// Replace each {{name}} in the template with its value, in one pass so that values containing
+// braces are left as they are
+fn fill_template(template: &str, values: &[(&str, String)]) -> String {
+    let mut html = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find("{{") {
+        let name_end = rest[start..].find("}}").map(|n| start + n);
+        let value = name_end.and_then(|end| {
+            values
+                .iter()
+                .find(|(name, _)| *name == &rest[start + 2..end])
+                .map(|(_, value)| value)
+        });
+
+        match (name_end, value) {
+            (Some(end), Some(value)) => {
+                html.push_str(&rest[..start]);
+                html.push_str(value);
+                rest = &rest[end + 2..];
+            }
+            _ => {
+                html.push_str(&rest[..start + 2]);
+                rest = &rest[start + 2..];
+            }
+        }
+    }
+
+    html.push_str(rest);
+    html
+}
+
+/// Renders the chart as a PNG bitmap
+#[cfg(feature = "png")]
+#[derive(Debug)]
+pub struct PngRenderer {
+    /// Add a resource table at the bottom of the chart
+    pub add_resource_table: bool,
+    /// The number of pixels for each pixel of the SVG image
+    pub scale: f32,
+    /// A CSS color to fill the image with, otherwise it is transparent
+    pub background: Option<String>,
+}
+
+#[cfg(feature = "png")]
+impl Renderer for PngRenderer {
+    fn render(
+        &self,
+        _schedule: &Schedule,
+        rd: &RenderData,
+        writer: &mut dyn Write,
+    ) -> Result<(), Box<dyn Error>> {
+        use resvg::tiny_skia;
+
+        let tree = svg_tree(
+            &SvgRenderer {
+                add_resource_table: self.add_resource_table,
+                background: self.background.clone(),
+            }
+            .document(rd)?,
+        )?;
+        let size = tree.size();
+        let mut pixmap = match tiny_skia::Pixmap::new(
+            (size.width() * self.scale).ceil() as u32,
+            (size.height() * self.scale).ceil() as u32,
+        ) {
+            Some(pixmap) => pixmap,
+            None => bail!("The PNG image would be empty or too large"),
+        };
+
+        resvg::render(
+            &tree,
+            tiny_skia::Transform::from_scale(self.scale, self.scale),
+            &mut pixmap.as_mut(),
+        );
+        writer.write_all(&pixmap.encode_png()?)?;
+
+        Ok(())
+    }
+}
+
+/// The size of a PDF page
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum PaperSize {
+    #[default]
+    A4,
+    A3,
+    Letter,
+    Legal,
+    Tabloid,
+}
+
+impl PaperSize {
+    /// The width and height of the paper in portrait orientation, in points
+    pub fn size(&self) -> (f32, f32) {
+        match self {
+            PaperSize::A4 => (595.28, 841.89),
+            PaperSize::A3 => (841.89, 1190.55),
+            PaperSize::Letter => (612.0, 792.0),
+            PaperSize::Legal => (612.0, 1008.0),
+            PaperSize::Tabloid => (792.0, 1224.0),
+        }
+    }
+}
+
+/// Which way up a PDF page is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum Orientation {
+    Portrait,
+    #[default]
+    Landscape,
+}
+
+/// Renders the chart as a PDF document, split into pages that each repeat the headings and titles
+#[cfg(feature = "pdf")]
+#[derive(Debug, Default)]
+pub struct PdfRenderer {
+    /// Add a resource table at the bottom of the chart
+    pub add_resource_table: bool,
+    pub paper: PaperSize,
+    pub orientation: Orientation,
+}
+
+#[cfg(feature = "pdf")]
+impl Renderer for PdfRenderer {
+    fn render(
+        &self,
+        _schedule: &Schedule,
+        rd: &RenderData,
+        writer: &mut dyn Write,
+    ) -> Result<(), Box<dyn Error>> {
+        use pdf_writer::{Content, Finish, Name, Pdf, Rect, Ref};
+        use std::collections::HashMap;
+
+        let tree = svg_tree(
+            &SvgRenderer {
+                add_resource_table: self.add_resource_table,
+                background: None,
+            }
+            .document(rd)?,
+        )?;
+
+        // The regions of the chart, in SVG pixels
+        let width = tree.size().width();
+        let height = tree.size().height();
+        let left = rd.gutter.left + rd.title_width;
+        let top = rd.gutter.top;
+        let heading_rows = if rd.groups.is_empty() { 1.0 } else { 2.0 };
+        let heading_top = rd.gutter.top - rd.row_height * heading_rows;
+        let rows_bottom = top + rd.rows.len() as f32 * rd.row_height;
+        let footer = height - rows_bottom;
+
+        // The printable area of the page, in points
+        let (paper_width, paper_height) = match self.orientation {
+            Orientation::Portrait => self.paper.size(),
+            Orientation::Landscape => {
+                let (width, height) = self.paper.size();
+                (height, width)
+            }
+        };
+        let margin = 36.0;
+        let page_width = paper_width - 2.0 * margin;
+        let page_height = paper_height - 2.0 * margin;
+
+        // SVG pixels are 96 to the inch, but shrink the chart if a page can't fit one month and one row
+        let max_col_width = rd.cols.iter().map(|col| col.width).fold(0.0, f32::max);
+        let scale = (0.75_f32)
+            .min(page_width / (left + max_col_width + rd.gutter.right))
+            .min(page_height / (top + rd.row_height + footer));
+
+        // Group the months and rows into pages
+        let mut col_ranges = vec![];
+        let mut x = left;
+
+        for col in rd.cols.iter() {
+            match col_ranges.last_mut() {
+                Some((start, end))
+                    if (x + col.width - *start) * scale <= page_width - left * scale =>
+                {
+                    *end = x + col.width
+                }
+                _ => col_ranges.push((x, x + col.width)),
+            }
+
+            x += col.width;
+        }
+
+        if let Some((start, end)) = col_ranges.last_mut() {
+            *end = (*start + (page_width / scale - left)).min(width);
+        }
+
+        let rows_per_page = (((page_height / scale - top) / rd.row_height).floor() as usize).max(1);
+        let mut row_ranges: Vec<(f32, f32)> = (0..rd.rows.len())
+            .step_by(rows_per_page)
+            .map(|i| {
+                (
+                    top + i as f32 * rd.row_height,
+                    top + (i + rows_per_page).min(rd.rows.len()) as f32 * rd.row_height,
+                )
+            })
+            .collect();
+
+        // The resource table goes under the last rows, or on a page of its own
+        match row_ranges.last() {
+            Some((start, _))
+                if (rows_bottom + footer - start) * scale <= page_height - top * scale => {}
+            _ => row_ranges.push((rows_bottom, rows_bottom)),
+        }
+
+        let mut pdf = Pdf::new();
+        let mut next_ref = Ref::new(1);
+        let catalog_ref = next_ref.bump();
+        let page_tree_ref = next_ref.bump();
+
+        // Draw the whole chart once, and show part of it on each page
+        let (chart, chart_ref) = svg2pdf::to_chunk(&tree, svg2pdf::ConversionOptions::default())
+            .map_err(|err| err.to_string())?;
+        let mut chart_refs = HashMap::new();
+        let chart = chart.renumber(|old| *chart_refs.entry(old).or_insert_with(|| next_ref.bump()));
+        let chart_ref = chart_refs[&chart_ref];
+        let chart_name = Name(b"Chart");
+        let mut page_refs = vec![];
+
+        pdf.extend(&chart);
+
+        for (i, (row_start, row_end)) in row_ranges.iter().enumerate() {
+            let last_rows = i == row_ranges.len() - 1;
+
+            for (col_start, col_end) in col_ranges.iter() {
+                let mut content = Content::new();
+                let mut page_y = paper_height - margin;
+                let body_x = margin + left * scale;
+
+                // Draw a rectangle of the chart in SVG pixels with its top left corner at a point on the page
+                let draw = |content: &mut Content,
+                            (x, y, w, h): (f32, f32, f32, f32),
+                            (page_x, page_y): (f32, f32)| {
+                    content.save_state();
+                    content.rect(page_x, page_y - h * scale, w * scale, h * scale);
+                    content.clip_nonzero();
+                    content.end_path();
+                    content.transform([
+                        width * scale,
+                        0.0,
+                        0.0,
+                        height * scale,
+                        page_x - x * scale,
+                        page_y - (height - y) * scale,
+                    ]);
+                    content.x_object(chart_name);
+                    content.restore_state();
+                };
+
+                // The title goes right across the page, and the headings and titles are on every page
+                draw(
+                    &mut content,
+                    (0.0, 0.0, page_width / scale, heading_top),
+                    (margin, page_y),
+                );
+                page_y -= heading_top * scale;
+                draw(
+                    &mut content,
+                    (0.0, heading_top, left, top - heading_top),
+                    (margin, page_y),
+                );
+                draw(
+                    &mut content,
+                    (
+                        *col_start,
+                        heading_top,
+                        col_end - col_start,
+                        top - heading_top,
+                    ),
+                    (body_x, page_y),
+                );
+                page_y -= (top - heading_top) * scale;
+                draw(
+                    &mut content,
+                    (0.0, *row_start, left, row_end - row_start),
+                    (margin, page_y),
+                );
+                draw(
+                    &mut content,
+                    (
+                        *col_start,
+                        *row_start,
+                        col_end - col_start,
+                        row_end - row_start,
+                    ),
+                    (body_x, page_y),
+                );
+
+                if last_rows {
+                    page_y -= (row_end - row_start) * scale;
+                    draw(
+                        &mut content,
+                        (0.0, rows_bottom, page_width / scale, footer),
+                        (margin, page_y),
+                    );
+                }
+
+                let page_ref = next_ref.bump();
+                let content_ref = next_ref.bump();
+                let mut page = pdf.page(page_ref);
+
+                page.media_box(Rect::new(0.0, 0.0, paper_width, paper_height))
+                    .parent(page_tree_ref)
+                    .contents(content_ref);
+                page.resources().x_objects().pair(chart_name, chart_ref);
+                page.finish();
+                pdf.stream(content_ref, &content.finish());
+                page_refs.push(page_ref);
+            }
+        }
+
+        pdf.catalog(catalog_ref).pages(page_tree_ref);
+        pdf.pages(page_tree_ref)
+            .count(page_refs.len() as i32)
+            .kids(page_refs);
+        writer.write_all(&pdf.finish())?;
+
+        Ok(())
+    }
+}
+
+// The characters used to draw the chart as text
+struct Glyphs {
+    grid: char,
+    marker: char,
+    closed: char,
+    open: char,
+    summary: char,
+    milestone: char,
+    // Separates the titles of critical items from their bars, when there are no colors to show them
+    critical: Option<char>,
+    // Blocks filling one to seven eighths of a character from the left, if there are any
+    eighths: Option<[char; 7]>,
+    // Ends text that has been cut short
+    more: char,
+    // Point to the parts of items before and after the chart
+    before: char,
+    after: char,
+}
+
+static TERM_GLYPHS: Glyphs = Glyphs {
+    grid: '│',
+    marker: '┊',
+    closed: '█',
+    open: '░',
+    summary: '▀',
+    milestone: '◆',
+    critical: None,
+    eighths: Some(['▏', '▎', '▍', '▌', '▋', '▊', '▉']),
+    more: '…',
+    before: '◀',
+    after: '▶',
+};
+
+static ASCII_GLYPHS: Glyphs = Glyphs {
+    grid: '|',
+    marker: ':',
+    closed: '#',
+    open: '-',
+    summary: '=',
+    milestone: '*',
+    critical: Some('!'),
+    eighths: None,
+    more: '~',
+    before: '<',
+    after: '>',
+};
+
+// How a character of the chart drawn as text is colored
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Ink {
+    Plain,
+    Faint,
+    Bold,
+    Resource(usize),
+    Late,
+    Early,
+}
+
+// A line of the chart drawn as text
+#[derive(Debug)]
+struct TextLine(Vec<(char, Ink)>);
+
+impl TextLine {
+    fn new(width: usize) -> TextLine {
+        TextLine(vec![(' ', Ink::Plain); width])
+    }
+
+    // The characters up to the last one that is not blank
+    fn trimmed(&self) -> &[(char, Ink)] {
+        let len = self
+            .0
+            .iter()
+            .rposition(|cell| *cell != (' ', Ink::Plain))
+            .map_or(0, |i| i + 1);
+
+        &self.0[..len]
+    }
+
+    fn is_blank(&self, at: usize) -> bool {
+        self.0.get(at) == Some(&(' ', Ink::Plain))
+    }
+
+    // Write text as far as the end of the line, returning the position after it
+    fn put(&mut self, at: usize, text: &str, ink: Ink) -> usize {
+        let mut at = at;
+
+        for c in text.chars() {
+            if let Some(cell) = self.0.get_mut(at) {
+                *cell = (c, ink);
+            }
+
+            at += 1;
+        }
+
+        at
+    }
+}
+
+// Text cut short to fit a number of characters
+fn fit_text(text: &str, chars: usize, more: char) -> String {
+    if text.chars().count() <= chars {
+        text.to_owned()
+    } else if chars == 0 {
+        String::new()
+    } else {
+        text.chars().take(chars - 1).chain([more]).collect()
+    }
+}
+
+// The fewest characters that fit a column heading and its grid line
+static MIN_TEXT_COLUMN_CHARS: usize = 4;
+
+// The number of characters for the item titles, and for the columns after them
+fn text_widths(rd: &RenderData, width: usize) -> (usize, usize) {
+    let title_chars = rd
+        .rows
+        .iter()
+        .map(|row| row.depth * 2 + row.title.chars().count() + 1)
+        .max()
+        .unwrap_or(0)
+        .min(width / 3)
+        .max(5);
+
+    (title_chars, width.saturating_sub(title_chars + 1))
+}
+
+/// Whether the columns of the chart are wide enough to read as text `width` characters wide
+pub(crate) fn text_columns_fit(rd: &RenderData, width: usize) -> bool {
+    rd.cols.len() * MIN_TEXT_COLUMN_CHARS <= text_widths(rd, width).1
+}
+
+// Draw the chart as lines of characters, with the month columns spread across the width
+fn text_lines(
+    rd: &RenderData,
+    width: usize,
+    add_resource_table: bool,
+    glyphs: &Glyphs,
+) -> Vec<TextLine> {
+    let (title_chars, body_chars) = text_widths(rd, width);
+    let body_chars = body_chars.max(rd.cols.len());
+    let origin = title_chars + 1;
+    let width = origin + body_chars;
+
+    // The first character of each column, scaled from the column widths
+    let total_width: f32 = rd.cols.iter().map(|col| col.width).sum();
+    let mut bounds = vec![0];
+    let mut x = 0.0;
+
+    for col in rd.cols.iter() {
+        x += col.width;
+        bounds.push((x / total_width * body_chars as f32).round() as usize);
+    }
+
+    // The position of an offset in the chart, in characters from the start of the first column,
+    // which is outside the columns for items that are cut off
+    let position = |offset: f32| {
+        let mut x = rd.gutter.left + rd.title_width;
+
+        for (i, col) in rd.cols.iter().enumerate() {
+            if offset < x + col.width || i == rd.cols.len() - 1 {
+                let chars = (bounds[i + 1] - bounds[i]) as f32;
+
+                return bounds[i] as f32 + (offset - x) / col.width * chars;
+            }
+
+            x += col.width;
+        }
+
+        0.0
+    };
+    let mut lines = vec![];
+    let mut line = TextLine::new(width);
+    let end = line.put(0, &fit_text(&rd.title, width, glyphs.more), Ink::Bold);
+
+    if let Some(progress) = rd.progress {
+        let complete = format!("{}% complete", progress);
+
+        if end + 1 + complete.len() <= width {
+            line.put(width - complete.len(), &complete, Ink::Plain);
+        }
+    }
+
+    lines.push(line);
+
+    // Label the groups of columns above the columns, where they have room
+    if !rd.groups.is_empty() {
+        let mut line = TextLine::new(width);
+        let mut x = rd.gutter.left + rd.title_width;
+
+        line.put(title_chars, &glyphs.grid.to_string(), Ink::Faint);
+
+        for (i, group) in rd.groups.iter().enumerate() {
+            let start = position(x).round() as usize;
+            let end = position(x + group.width).round() as usize;
+            let mut at = origin + start;
+
+            if i > 0 {
+                line.put(at, &glyphs.grid.to_string(), Ink::Faint);
+                at += 1;
+            }
+
+            if group.label.len() < end - start {
+                line.put(at, &group.label, Ink::Bold);
+            }
+
+            x += group.width;
+        }
+
+        lines.push(line);
+    }
+
+    let mut line = TextLine::new(width);
+
+    line.put(0, "Tasks", Ink::Bold);
+    line.put(title_chars, &glyphs.grid.to_string(), Ink::Faint);
+
+    for (i, col) in rd.cols.iter().enumerate() {
+        let mut at = origin + bounds[i];
+        let mut chars = bounds[i + 1] - bounds[i];
+
+        if i > 0 {
+            line.put(at, &glyphs.grid.to_string(), Ink::Faint);
+            at += 1;
+            chars = chars.saturating_sub(1);
+        }
+
+        line.put(at, &fit_text(&col.label, chars, glyphs.more), Ink::Bold);
+    }
+
+    lines.push(line);
+
+    for row in rd.rows.iter() {
+        let mut line = TextLine::new(width);
+        let title = format!("{}{}", "  ".repeat(row.depth), row.title);
+        let ink = if row.critical {
+            Ink::Late
+        } else if row.summary {
+            Ink::Bold
+        } else {
+            Ink::Plain
+        };
+
+        line.put(0, &fit_text(&title, title_chars, glyphs.more), ink);
+        line.put(
+            title_chars,
+            &glyphs
+                .critical
+                .filter(|_| row.critical)
+                .unwrap_or(glyphs.grid)
+                .to_string(),
+            Ink::Faint,
+        );
+
+        let start = position(row.offset).clamp(0.0, body_chars as f32);
+        let mut bar_end = start.floor() as usize;
+        // Items entirely outside the chart only get an arrow pointing to them
+        let outside = |offset: f32| {
+            (row.clipped_start && position(offset) <= 0.0)
+                || (row.clipped_end && position(offset) >= body_chars as f32)
+        };
+
+        match row.length {
+            _ if outside(row.offset) && outside(row.offset + row.length.unwrap_or(0.0)) => {}
+            Some(length) => {
+                let (bar_start, bar_finish) = (position(row.offset), position(row.offset + length));
+                let end = bar_finish.clamp(start, body_chars as f32);
+                let done = row
+                    .progress
+                    .map(|p| bar_start + (bar_finish - bar_start) * p as f32 / 100.0);
+                let (glyph, ink) = if row.summary {
+                    (glyphs.summary, Ink::Bold)
+                } else {
+                    (
+                        if row.open { glyphs.open } else { glyphs.closed },
+                        row.assignments.first().map_or(Ink::Plain, |assignment| {
+                            Ink::Resource(assignment.resource_index)
+                        }),
+                    )
+                };
+
+                for i in (start.floor() as usize)..(end.ceil() as usize).min(body_chars) {
+                    let cover = end.min(i as f32 + 1.0) - start.max(i as f32);
+                    let glyph = match done {
+                        Some(done) if !row.summary => {
+                            if (i as f32 + 0.5) < done {
+                                glyphs.closed
+                            } else {
+                                glyphs.open
+                            }
+                        }
+                        _ => glyph,
+                    };
+                    let eighths = (cover * 8.0).round() as usize;
+                    let c = match glyphs.eighths {
+                        _ if eighths >= 8 => glyph,
+                        // Only the end of a bar can be part of a character
+                        Some(blocks)
+                            if glyph == glyphs.closed
+                                && start <= i as f32
+                                && (1..8).contains(&eighths) =>
+                        {
+                            blocks[eighths - 1]
+                        }
+                        _ if cover >= 0.5 => glyph,
+                        _ => continue,
+                    };
+
+                    line.put(origin + i, &c.to_string(), ink);
+                    bar_end = i + 1;
+                }
+
+                // Even the shortest bar shows up
+                if bar_end <= start.floor() as usize {
+                    let i = (((start + end) / 2.0) as usize).min(body_chars - 1);
+
+                    line.put(origin + i, &glyph.to_string(), ink);
+                    bar_end = i + 1;
+                }
+            }
+            None => {
+                let i = (start as usize).min(body_chars - 1);
+
+                line.put(origin + i, &glyphs.milestone.to_string(), Ink::Bold);
+                bar_end = i + 1;
+            }
+        }
+
+        if row.clipped_start {
+            line.put(origin, &glyphs.before.to_string(), Ink::Plain);
+            bar_end = bar_end.max(1);
+        }
+
+        if row.clipped_end {
+            line.put(
+                origin + body_chars - 1,
+                &glyphs.after.to_string(),
+                Ink::Plain,
+            );
+        }
+
+        // Label the bar if there is room after it
+        let mut labels = vec![];
+
+        if let Some(progress) = row.progress {
+            labels.push((format!("{}%", progress), Ink::Plain));
+        }
+
+        if let Some(slip) = row.slip {
+            labels.push(if slip > 0 {
+                (format!("+{}d", slip), Ink::Late)
+            } else {
+                (format!("{}d", slip), Ink::Early)
+            });
+        }
+
+        let mut at = origin + bar_end + 1;
+
+        for (label, ink) in labels {
+            if at + label.chars().count() <= width {
+                at = line.put(at, &label, ink) + 1;
+            }
+        }
+
+        if let Some(offset) = rd.marked_date_offset {
+            let at = origin + (position(offset) as usize).min(body_chars - 1);
+
+            if line.is_blank(at) {
+                line.put(at, &glyphs.marker.to_string(), Ink::Faint);
+            }
+        }
+
+        for bound in bounds.iter().take(rd.cols.len()).skip(1) {
+            if line.is_blank(origin + bound) {
+                line.put(origin + bound, &glyphs.grid.to_string(), Ink::Faint);
+            }
+        }
+
+        lines.push(line);
+    }
+
+    if add_resource_table && !rd.resources.is_empty() {
+        lines.push(TextLine::new(width));
+
+        let mut line = TextLine::new(width);
+        let mut at = 0;
+
+        for (i, resource) in rd.resources.iter().enumerate() {
+            let days = rd.resource_days[i];
+            let text = if days.fract() == 0.0 {
+                format!("{} ({}d)", resource, days)
+            } else {
+                format!("{} ({:.1}d)", resource, days)
+            };
+
+            if at > 0 && at + 2 + text.chars().count() > width {
+                lines.push(line);
+                line = TextLine::new(width);
+                at = 0;
+            }
+
+            line.put(at, &glyphs.closed.to_string(), Ink::Resource(i));
+            at = line.put(at + 2, &text, Ink::Plain) + 2;
+        }
+
+        lines.push(line);
+    }
+
+    lines
+}
+
+// The terminal color for a CSS color
+fn term_color(color: &str) -> Option<colored::Color> {
+    let hex = match color.strip_prefix('#') {
+        Some(hex) if hex.len() == 3 => hex.chars().flat_map(|c| [c, c]).collect(),
+        Some(hex) => hex.to_owned(),
+        None => return color.parse().ok(),
+    };
+    let rgb = u32::from_str_radix(&hex, 16)
+        .ok()
+        .filter(|_| hex.len() == 6)?;
+
+    Some(colored::Color::TrueColor {
+        r: (rgb >> 16) as u8,
+        g: (rgb >> 8) as u8,
+        b: rgb as u8,
+    })
+}
+
+/// Renders the chart as colored text for a terminal
+#[derive(Debug)]
+pub struct TermRenderer {
+    /// Add a resource table at the bottom of the chart
+    pub add_resource_table: bool,
+    /// The number of characters in each line
+    pub width: usize,
+}
+
+impl TermRenderer {
+    /// The number of characters across the terminal, if there is one
+    pub fn terminal_width() -> Option<usize> {
+        match terminal_size::terminal_size() {
+            Some((terminal_size::Width(width), _)) if width > 0 => Some(width as usize),
+            _ => std::env::var("COLUMNS").ok()?.parse().ok(),
+        }
+    }
+}
+
+impl Renderer for TermRenderer {
+    fn render(
+        &self,
+        _schedule: &Schedule,
+        rd: &RenderData,
+        writer: &mut dyn Write,
+    ) -> Result<(), Box<dyn Error>> {
+        use colored::Colorize;
+
+        let colors: Vec<Option<colored::Color>> = rd
+            .resource_colors
+            .iter()
+            .map(|color| term_color(color))
+            .collect();
+
+        for line in text_lines(rd, self.width, self.add_resource_table, &TERM_GLYPHS) {
+            let mut cells = line.trimmed().iter().peekable();
+
+            // Color each run of characters with the same ink together
+            while let Some(&(c, ink)) = cells.next() {
+                let mut text = c.to_string();
+
+                while let Some((c, _)) = cells.next_if(|(_, next)| *next == ink) {
+                    text.push(*c);
+                }
+
+                let text = match ink {
+                    Ink::Plain => text.normal(),
+                    Ink::Faint => text.dimmed(),
+                    Ink::Bold => text.bold(),
+                    Ink::Resource(i) => match colors.get(i) {
+                        Some(Some(color)) => text.color(*color),
+                        _ => text.normal(),
+                    },
+                    Ink::Late => text.red(),
+                    Ink::Early => text.green(),
+                };
+
+                write!(writer, "{}", text)?;
+            }
+
+            writeln!(writer)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Renders the chart as plain ASCII text that is the same every time, for pasting into emails and reviews
+#[derive(Debug)]
+pub struct AsciiRenderer {
+    /// Add a resource table at the bottom of the chart
+    pub add_resource_table: bool,
+    /// The number of characters in each line
+    pub width: usize,
+}
+
+impl Renderer for AsciiRenderer {
+    fn render(
+        &self,
+        _schedule: &Schedule,
+        rd: &RenderData,
+        writer: &mut dyn Write,
+    ) -> Result<(), Box<dyn Error>> {
+        for line in text_lines(rd, self.width, self.add_resource_table, &ASCII_GLYPHS) {
+            let text: String = line.trimmed().iter().map(|(c, _)| c).collect();
+
+            writeln!(writer, "{}", text)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fill_template_leaves_placeholders_in_values() {
+        let values = [
+            ("title", "T {{svg}}".to_owned()),
+            ("svg", "<svg/>".to_owned()),
+        ];
+
+        assert_eq!(
+            fill_template("<title>{{title}}</title>{{svg}}{{other}}{{", &values),
+            "<title>T {{svg}}</title><svg/>{{other}}{{"
+        );
+    }
+}
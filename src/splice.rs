@@ -0,0 +1,365 @@
+use crate::BaselineData;
+use easy_error::bail;
+use std::{error::Error, ops::Range};
+
+// A member of a JSON5 object or array, as positions in the text
+#[derive(Debug)]
+struct Member {
+    key: Option<String>,
+    range: Range<usize>,
+    value_start: usize,
+}
+
+// The index of the next character that is not whitespace or part of a comment
+fn skip_space(text: &[u8], mut i: usize) -> usize {
+    loop {
+        while i < text.len() && text[i].is_ascii_whitespace() {
+            i += 1;
+        }
+
+        if text[i..].starts_with(b"//") {
+            while i < text.len() && text[i] != b'\n' {
+                i += 1;
+            }
+        } else if text[i..].starts_with(b"/*") {
+            i = match text[i + 2..].windows(2).position(|w| w == b"*/") {
+                Some(n) => i + 2 + n + 2,
+                None => text.len(),
+            };
+        } else {
+            return i;
+        }
+    }
+}
+
+// The index just past the value or key starting at `i`
+fn skip_value(text: &[u8], i: usize) -> Result<usize, Box<dyn Error>> {
+    match text.get(i) {
+        Some(b'{') | Some(b'[') => Ok(members(text, i)?.1 + 1),
+        Some(&quote) if quote == b'"' || quote == b'\'' => {
+            let mut j = i + 1;
+
+            while j < text.len() {
+                if text[j] == b'\\' {
+                    j += 2;
+                } else if text[j] == quote {
+                    return Ok(j + 1);
+                } else {
+                    j += 1;
+                }
+            }
+
+            bail!("Unterminated string in JSON5 file")
+        }
+        Some(_) => {
+            let mut j = i;
+
+            while j < text.len()
+                && !b",:]}".contains(&text[j])
+                && !text[j].is_ascii_whitespace()
+                && !text[j..].starts_with(b"//")
+                && !text[j..].starts_with(b"/*")
+            {
+                j += 1;
+            }
+
+            if j == i {
+                bail!("Unexpected '{}' in JSON5 file", text[i] as char);
+            }
+
+            Ok(j)
+        }
+        None => bail!("Unexpected end of JSON5 file"),
+    }
+}
+
+// The members of the object or array opening at `open`, and the index of its closing bracket
+fn members(text: &[u8], open: usize) -> Result<(Vec<Member>, usize), Box<dyn Error>> {
+    let is_object = text[open] == b'{';
+    let close = if is_object { b'}' } else { b']' };
+    let mut members = vec![];
+    let mut i = open + 1;
+
+    loop {
+        i = skip_space(text, i);
+
+        match text.get(i) {
+            None => bail!("Unexpected end of JSON5 file"),
+            Some(&c) if c == close => return Ok((members, i)),
+            _ => (),
+        }
+
+        let start = i;
+        let mut key = None;
+
+        if is_object {
+            i = skip_value(text, i)?;
+
+            let key_text = std::str::from_utf8(&text[start..i])?;
+
+            key = Some(if key_text.starts_with(['"', '\'']) {
+                json5::from_str::<String>(key_text)?
+            } else {
+                key_text.to_owned()
+            });
+            i = skip_space(text, i);
+
+            if text.get(i) != Some(&b':') {
+                bail!("Expected ':' after '{}' in JSON5 file", key_text);
+            }
+
+            i = skip_space(text, i + 1);
+        }
+
+        let value_start = i;
+
+        i = skip_value(text, i)?;
+        members.push(Member {
+            key,
+            range: start..i,
+            value_start,
+        });
+        i = skip_space(text, i);
+
+        match text.get(i) {
+            Some(b',') => i += 1,
+            Some(&c) if c == close => (),
+            _ => bail!("Expected ',' or '{}' in JSON5 file", close as char),
+        }
+    }
+}
+
+// The whitespace before `i` on its line
+fn indent_at(text: &str, i: usize) -> &str {
+    let line_start = text[..i].rfind('\n').map_or(0, |n| n + 1);
+    let indent = &text[line_start..i];
+
+    if indent.trim().is_empty() {
+        indent
+    } else {
+        ""
+    }
+}
+
+// Add a member after the last one, on a line of its own after any comment ending the line
+fn append_member(text: &mut String, last_end: usize, indent: &str, member: &str) {
+    let comma = skip_space(text.as_bytes(), last_end);
+
+    let mut i = if text.as_bytes().get(comma) == Some(&b',') {
+        comma + 1
+    } else {
+        text.insert(last_end, ',');
+        last_end + 1
+    };
+
+    while text[i..].starts_with([' ', '\t']) {
+        i += 1;
+    }
+
+    if text[i..].starts_with("//") {
+        i += text[i..].find('\n').unwrap_or(text.len() - i);
+    }
+
+    text.insert_str(i, &format!("\n{}{},", indent, member));
+}
+
+fn format_baseline(baseline: &BaselineData, indent: &str) -> Result<String, Box<dyn Error>> {
+    let mut lines = vec![
+        "{".to_owned(),
+        format!("{}  name: {},", indent, json5::to_string(&baseline.name)?),
+        format!("{}  items: [", indent),
+    ];
+
+    for item in baseline.items.iter() {
+        lines.push(format!(
+            "{}    {{ id: {}, startDate: \"{}\", endDate: \"{}\" }},",
+            indent,
+            json5::to_string(&item.id)?,
+            item.start_date,
+            item.end_date
+        ));
+    }
+
+    lines.push(format!("{}  ],", indent));
+    lines.push(format!("{}}}", indent));
+
+    Ok(lines.join("\n"))
+}
+
+/// Add a baseline to the `baselines` of the JSON5 object in `text`, replacing any baseline with
+/// the same name. Everything else in the text, including comments, is left as it is.
+pub fn splice_baseline(text: &str, baseline: &BaselineData) -> Result<String, Box<dyn Error>> {
+    let bytes = text.as_bytes();
+    let open = skip_space(bytes, 0);
+
+    if bytes.get(open) != Some(&b'{') {
+        bail!("The JSON5 file must contain an object");
+    }
+
+    let (root, _) = members(bytes, open)?;
+    let mut text = text.to_owned();
+
+    match root
+        .iter()
+        .find(|member| member.key.as_deref() == Some("baselines"))
+    {
+        Some(member) => {
+            if bytes[member.value_start] != b'[' {
+                bail!("The baselines in the JSON5 file must be an array");
+            }
+
+            let (elements, _) = members(bytes, member.value_start)?;
+            let indent = match elements.first() {
+                Some(element) => indent_at(&text, element.range.start).to_owned(),
+                None => format!("{}  ", indent_at(&text, member.range.start)),
+            };
+            let new_baseline = format_baseline(baseline, &indent)?;
+            let existing = elements.iter().find(|element| {
+                json5::from_str::<BaselineData>(&text[element.range.clone()])
+                    .is_ok_and(|existing| existing.name == baseline.name)
+            });
+
+            match (existing, elements.last()) {
+                (Some(element), _) => text.replace_range(element.range.clone(), &new_baseline),
+                (None, Some(element)) => {
+                    append_member(&mut text, element.range.end, &indent, &new_baseline)
+                }
+                (None, None) => text.insert_str(
+                    member.value_start + 1,
+                    &format!(
+                        "\n{}{},\n{}",
+                        indent,
+                        new_baseline,
+                        indent_at(&text, member.range.start)
+                    ),
+                ),
+            }
+        }
+        None => {
+            let indent = match root.first() {
+                Some(member) => indent_at(&text, member.range.start).to_owned(),
+                None => "  ".to_owned(),
+            };
+            let baselines = format!(
+                "baselines: [\n{0}  {1},\n{0}]",
+                indent,
+                format_baseline(baseline, &format!("{}  ", indent))?
+            );
+
+            match root.last() {
+                Some(member) => append_member(&mut text, member.range.end, &indent, &baselines),
+                None => {
+                    let close =
+                        open + 1 + text[open + 1..].len() - text[open + 1..].trim_start().len();
+
+                    text.replace_range(open + 1..close, &format!("\n{}{},\n", indent, baselines))
+                }
+            }
+        }
+    }
+
+    Ok(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BaselineItemData;
+    use chrono::NaiveDate;
+
+    fn baseline(name: &str) -> BaselineData {
+        BaselineData {
+            name: name.to_owned(),
+            items: vec![BaselineItemData {
+                id: "a".to_owned(),
+                start_date: NaiveDate::from_ymd_opt(2026, 10, 12).unwrap(),
+                end_date: NaiveDate::from_ymd_opt(2026, 10, 19).unwrap(),
+            }],
+        }
+    }
+
+    const SAVED: &str = r#"{
+      name: "v1",
+      items: [
+        { id: "a", startDate: "2026-10-12", endDate: "2026-10-19" },
+      ],
+    }"#;
+
+    fn splice(text: &str, name: &str) -> String {
+        splice_baseline(text, &baseline(name)).unwrap()
+    }
+
+    #[test]
+    fn keeps_comments_after_last_member() {
+        assert_eq!(
+            splice("{\n  title: \"T\", // the title\n  items: [], // no items\n}\n", "v1"),
+            format!(
+                "{{\n  title: \"T\", // the title\n  items: [], // no items\n  baselines: [\n    {},\n  ],\n}}\n",
+                SAVED
+            )
+        );
+    }
+
+    #[test]
+    fn adds_comma_after_last_member() {
+        assert_eq!(
+            splice("{\n  title: \"T\",\n  items: []\n}\n", "v1"),
+            format!(
+                "{{\n  title: \"T\",\n  items: [],\n  baselines: [\n    {},\n  ],\n}}\n",
+                SAVED
+            )
+        );
+    }
+
+    #[test]
+    fn fills_empty_baselines() {
+        assert_eq!(
+            splice("{\n  title: \"T\",\n  baselines: [],\n}\n", "v1"),
+            format!(
+                "{{\n  title: \"T\",\n  baselines: [\n    {},\n  ],\n}}\n",
+                SAVED
+            )
+        );
+    }
+
+    #[test]
+    fn replaces_baseline_with_same_name() {
+        let text = "{\n  baselines: [\n    { name: \"v1\", items: [] },\n    { name: \"v2\", items: [] },\n  ],\n}\n";
+
+        assert_eq!(
+            splice(text, "v1"),
+            format!(
+                "{{\n  baselines: [\n    {},\n    {{ name: \"v2\", items: [] }},\n  ],\n}}\n",
+                SAVED
+            )
+        );
+    }
+
+    #[test]
+    fn appends_to_quoted_baselines_key() {
+        let text = "{\n  \"title\": \"T\",\n  \"baselines\": [\n    { \"name\": \"v0\", \"items\": [] }\n  ]\n}\n";
+
+        assert_eq!(
+            splice(text, "v1"),
+            format!(
+                "{{\n  \"title\": \"T\",\n  \"baselines\": [\n    {{ \"name\": \"v0\", \"items\": [] }},\n    {},\n  ]\n}}\n",
+                SAVED
+            )
+        );
+    }
+
+    #[test]
+    fn adds_baselines_to_empty_object() {
+        let expected = format!("{{\n  baselines: [\n    {},\n  ],\n}}\n", SAVED);
+
+        assert_eq!(splice("{}\n", "v1"), expected);
+        assert_eq!(splice("{\n}\n", "v1"), expected);
+    }
+
+    #[test]
+    fn rejects_files_that_are_not_objects() {
+        assert!(splice_baseline("[]", &baseline("v1")).is_err());
+        assert!(splice_baseline("{ baselines: {} }", &baseline("v1")).is_err());
+        assert!(splice_baseline("{ title: \"T\"", &baseline("v1")).is_err());
+    }
+}